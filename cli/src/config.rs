@@ -0,0 +1,83 @@
+//! Defaults for command line flags, read from the environment and a config file.
+
+use anyhow::Context;
+use log::warn;
+use std::path::{Path, PathBuf};
+
+/// The number of results shown when nothing says otherwise.
+const DEFAULT_LIMIT: usize = 10;
+
+/// The environment variable overriding the config file's `limit`.
+const LIMIT_VAR: &str = "ANGLO_SAXON_LIMIT";
+
+/// Settings from the config file at `config_path`. It holds `key = value` lines, with blank
+/// lines and those starting with `#` ignored:
+///
+/// ```text
+/// # Show more results than the built-in 10
+/// limit = 25
+/// ```
+#[derive(Debug, Default)]
+pub struct Config {
+    /// The number of results to show when --limit isn't given.
+    pub limit: Option<usize>,
+}
+
+/// `$XDG_CONFIG_HOME/anglo-saxon-dictionary/config`, or `~/.config/anglo-saxon-dictionary/config`
+/// when `XDG_CONFIG_HOME` isn't set.
+pub fn config_path() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("anglo-saxon-dictionary").join("config"))
+}
+
+impl Config {
+    /// Read the config file, if there is one.
+    pub fn load() -> anyhow::Result<Self> {
+        let Some(path) = config_path() else {
+            return Ok(Config::default());
+        };
+        match std::fs::read_to_string(&path) {
+            Ok(text) => Self::parse(&text, &path),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Config::default()),
+            Err(e) => Err(e).with_context(|| format!("Couldn't read {}", path.display())),
+        }
+    }
+
+    fn parse(text: &str, path: &Path) -> anyhow::Result<Self> {
+        let mut config = Config::default();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                anyhow::bail!("{}:{}: expected `key = value`", path.display(), i + 1);
+            };
+            match key.trim() {
+                "limit" => {
+                    let limit = value.trim().parse().with_context(|| {
+                        format!("{}:{}: limit must be a number", path.display(), i + 1)
+                    })?;
+                    config.limit = Some(limit);
+                }
+                key => warn!("Ignoring unknown setting {:?} in {}", key, path.display()),
+            }
+        }
+        Ok(config)
+    }
+
+    /// The number of results to show without --limit: `ANGLO_SAXON_LIMIT` if it's set, then
+    /// the config file's `limit`, then 10.
+    pub fn default_limit(&self) -> anyhow::Result<usize> {
+        match std::env::var(LIMIT_VAR) {
+            Ok(value) => value
+                .trim()
+                .parse()
+                .with_context(|| format!("{} must be a number, not {:?}", LIMIT_VAR, value)),
+            Err(_) => Ok(self.limit.unwrap_or(DEFAULT_LIMIT)),
+        }
+    }
+}
@@ -1,56 +1,466 @@
-use anyhow;
-use clap::{Parser, Subcommand};
+use anglo_saxon_dict_parser::{
+    compare_old_english, is_runic, sort_by_ordinal, transliterate_futhorc, Dictionary, Entry,
+    FetchOptions, MatchKind, ParseReport, Progress, Snippet, UrlCache,
+};
+use anyhow::Context;
+use clap::error::ErrorKind;
+use clap::{ArgAction, CommandFactory, Parser, Subcommand, ValueEnum};
+use clap_complete::Shell;
 use colored::Colorize;
-use std::path::PathBuf;
+use comfy_table::presets::UTF8_FULL_CONDENSED;
+use comfy_table::{Attribute, Cell, Color, ContentArrangement, Table};
+use config::Config;
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+use log::{info, LevelFilter};
+use std::borrow::Cow;
+use std::io::{BufRead, IsTerminal, Read, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 use tokio::sync::oneshot;
 use tokio::time::{interval, Duration};
-use url;
-use std::io::{self, Write};
+
+mod config;
+#[cfg(unix)]
+mod pager;
+
+/// The Project Gutenberg dictionary, gzipped, embedded by the `bundled` feature.
+#[cfg(feature = "bundled")]
+static BUILTIN_HTML: &[u8] = include_bytes!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/assets/pg31543-images.html.gz"
+));
+
+/// The `--max-def-len` to cut definitions shown as text to, if given.
+static MAX_DEFINITION_LEN: OnceLock<usize> = OnceLock::new();
+
+/// Whether stdout is a terminal, and its width, as they were at startup before any pager took
+/// stdout over.
+static TERMINAL: OnceLock<Option<Option<u16>>> = OnceLock::new();
+
+/// The width of the terminal stdout was connected to at startup, or `None` when it wasn't a
+/// terminal. The inner `None` is a terminal of unknown width.
+fn terminal() -> Option<Option<u16>> {
+    *TERMINAL.get_or_init(|| {
+        std::io::stdout()
+            .is_terminal()
+            .then(|| Table::new().width())
+    })
+}
 
 /// The command line options
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 struct Cli {
-    /// The HTML file to parse
-    #[arg(long, short)]
+    /// The HTML file to parse. HTML is read from stdin if neither this nor --url is given
+    #[arg(long, short, conflicts_with = "url")]
     file: Option<PathBuf>,
 
-    /// The HTML file to parse
+    /// The URL of the HTML file to parse
     #[arg(long, short)]
     url: Option<url::Url>,
 
+    /// An index directory written by the build command, used instead of parsing HTML
+    #[arg(long, conflicts_with_all = ["file", "url"])]
+    index: Option<PathBuf>,
+
+    /// Use the dictionary built into this binary. This is the default when no other source
+    /// is given and stdin is a terminal
+    #[cfg(feature = "bundled")]
+    #[arg(long, conflicts_with_all = ["file", "url", "index"])]
+    builtin: bool,
+
+    /// Always download --url rather than reading it from the cache
+    #[arg(long)]
+    no_cache: bool,
+
+    /// Refetch --url when the cached copy is older than this many seconds
+    #[arg(long, value_name = "SECONDS")]
+    cache_ttl: Option<u64>,
+
+    /// Give up on a --url request after this many seconds
+    #[arg(long, value_name = "SECONDS", default_value_t = 30)]
+    timeout: u64,
+
+    /// Retry a --url request this many times when it fails to connect, times out or the server
+    /// errors
+    #[arg(long, default_value_t = 2)]
+    retries: u32,
+
+    /// Label every parsed entry with the dictionary it came from, e.g. bosworth-toller, for
+    /// search --from to pick out
+    #[arg(long, value_name = "LABEL", conflicts_with = "index")]
+    source: Option<String>,
+
+    /// The output format for results
+    #[arg(long, global = true, value_enum, default_value_t = Format::Text)]
+    format: Format,
+
+    /// When to color text output. `auto` colors only when stdout is a terminal and NO_COLOR
+    /// isn't set
+    #[arg(long, global = true, value_enum, default_value_t = ColorChoice::Auto)]
+    color: ColorChoice,
+
+    /// Shorthand for --color never
+    #[arg(long, global = true, conflicts_with = "color")]
+    no_color: bool,
+
+    /// Cut each definition shown as text or in a table to N characters, ending it with "…".
+    /// JSON and CSV output keep the whole definition
+    #[arg(long, global = true, value_name = "N")]
+    max_def_len: Option<usize>,
+
+    /// Show search, define and list results through $PAGER, or less if it isn't set. Ignored
+    /// when stdout isn't a terminal
+    #[arg(long, global = true)]
+    pager: bool,
+
+    /// Exit with status 1 when search, define or list finds nothing for a term
+    #[arg(long, global = true)]
+    fail_on_empty: bool,
+
+    /// Report how many paragraphs were parsed and skipped, and why, on stderr. Also shows log
+    /// messages: info with -v, debug with -vv and trace with -vvv. RUST_LOG is used otherwise
+    #[arg(long, short, global = true, action = ArgAction::Count)]
+    verbose: u8,
+
+    /// Show no log messages or progress, only results and errors
+    #[arg(long, short, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+
     #[command(subcommand)]
     command: Commands,
 }
 
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    /// Colorized, human readable text
+    Text,
+    /// A JSON array of entries
+    Json,
+    /// Comma separated values with a header row
+    Csv,
+    /// A table of words and definitions, wrapped to the terminal's width. Plain text when
+    /// stdout isn't a terminal
+    Table,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Sort {
+    /// Best match first
+    Relevance,
+    /// By headword, in Old English alphabetical order
+    Alphabetical,
+    /// In the order entries appear in the dictionary
+    Source,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ExportFormat {
+    /// One JSON object per line, after a line naming the format version
+    Jsonl,
+    /// One JSON object holding the format version and an array of entries
+    Json,
+    /// StarDict's .ifo, .idx and .dict files, written to --out
+    Stardict,
+    /// An `entries` table in a SQLite database, written to --out
+    Sqlite,
+    /// Each distinct headword on its own line, alphabetically, e.g. for a spellchecker
+    Wordlist,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    fn enabled(self) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty()) && terminal().is_some()
+            }
+        }
+    }
+}
+
 #[derive(Subcommand)]
 enum Commands {
-    /// Find words by English translation
-    Search { term: String },
+    /// Find words by English translation, for each term given
+    Search {
+        #[arg(value_name = "TERM", required_unless_present = "terms_file")]
+        terms: Vec<String>,
+
+        /// Also search each non-blank line of FILE
+        #[arg(long, value_name = "FILE")]
+        terms_file: Option<PathBuf>,
+
+        /// Only show entries with this part of speech, as abbreviated in the dictionary, e.g. m.
+        #[arg(long, conflicts_with_all = ["fuzzy", "snippets"])]
+        pos: Option<String>,
+
+        /// Match headwords within N edits of the term instead
+        #[arg(long, value_name = "N")]
+        fuzzy: Option<u8>,
+
+        /// Show the part of each definition around the match, with the match highlighted
+        #[arg(long, conflicts_with = "fuzzy")]
+        snippets: bool,
+
+        /// Match definitions only, to find the words an English term translates to
+        #[arg(long, conflicts_with_all = ["fuzzy", "snippets", "pos"])]
+        gloss: bool,
+
+        /// Only show entries parsed with this --source label
+        #[arg(long, value_name = "LABEL", conflicts_with_all = ["fuzzy", "snippets", "pos", "gloss"])]
+        from: Option<String>,
+
+        /// The maximum number of results to show for each term. Defaults to ANGLO_SAXON_LIMIT,
+        /// then the config file's `limit`, then 10
+        #[arg(long, short)]
+        limit: Option<usize>,
+
+        /// The order to list results in
+        #[arg(long, value_enum, default_value_t = Sort::Relevance)]
+        sort: Sort,
+    },
+
+    /// Show the definition for the given term, followed by those of close matches, marked ~
+    Define {
+        term: String,
+
+        /// The maximum number of definitions to show. Defaults to ANGLO_SAXON_LIMIT, then the
+        /// config file's `limit`, then 10
+        #[arg(long, short)]
+        limit: Option<usize>,
+
+        /// Match headwords within N edits of the term instead
+        #[arg(long, value_name = "N")]
+        fuzzy: Option<u8>,
+
+        /// Only match headwords with exactly the term's capitalization and spelling
+        #[arg(long, conflicts_with = "fuzzy")]
+        case_sensitive: bool,
+
+        /// Only show entries with this part of speech, as abbreviated in the dictionary, e.g. m.
+        #[arg(long, conflicts_with_all = ["fuzzy", "case_sensitive"])]
+        pos: Option<String>,
+    },
+
+    /// Show every entry whose headword begins with the given prefix, alphabetically
+    List {
+        prefix: String,
+
+        /// Match the prefix anywhere in the headword rather than only at its start
+        #[arg(long)]
+        contains: bool,
+    },
+
+    /// Show a random entry, e.g. as a word of the day
+    Random {
+        /// Pick the same entry on every run by seeding the choice
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+
+    /// List headwords beginning with the given prefix
+    Suggest {
+        prefix: String,
+
+        /// The maximum number of headwords to list
+        #[arg(long, short, default_value_t = 10)]
+        limit: usize,
+    },
+
+    /// Load the dictionary once and answer queries read from stdin.
+    ///
+    /// Each line is searched by default. Prefix a line with `/def` to define it instead, or
+    /// enter `/def` or `/search` alone to switch the default. Enter `:quit` to exit.
+    Repl,
+
+    /// Parse the dictionary once and write a reusable index to OUT, for later use with --index
+    Build { out: PathBuf },
+
+    /// Answer searches and definitions over HTTP, as JSON, until interrupted
+    Serve {
+        /// The address to listen on
+        #[arg(long, default_value = "127.0.0.1:8080")]
+        addr: String,
+    },
+
+    /// Write every entry in the dictionary to stdout, or to --out for StarDict and SQLite
+    Export {
+        /// The format to write entries in
+        #[arg(
+            id = "export_format",
+            value_name = "FORMAT",
+            value_enum,
+            default_value_t = ExportFormat::Jsonl
+        )]
+        format: ExportFormat,
+
+        /// The directory to write StarDict files to, or the SQLite database to write
+        #[arg(
+            long,
+            value_name = "PATH",
+            required_if_eq_any([("export_format", "stardict"), ("export_format", "sqlite")])
+        )]
+        out: Option<PathBuf>,
+
+        /// For the word list, strip diacritics and spell æ as ae and þ and ð as th
+        #[arg(long)]
+        normalize: bool,
+    },
 
-    /// Show the definition for the given term
-    Define { term: String },
+    /// Print a shell completion script to stdout
+    Completions {
+        /// The shell to generate the script for
+        #[arg(value_enum)]
+        shell: Shell,
+    },
 }
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     let cli = Cli::parse();
+    init_logging(cli.verbose, cli.quiet);
+    let default_limit = Config::load()?.default_limit()?;
+    let color = if cli.no_color {
+        ColorChoice::Never
+    } else {
+        cli.color
+    };
+    colored::control::set_override(color.enabled());
+    if let Some(max) = cli.max_def_len {
+        let _ = MAX_DEFINITION_LEN.set(max);
+    }
+    if let Commands::Completions { shell } = cli.command {
+        let mut command = Cli::command();
+        let name = command.get_name().to_string();
+        clap_complete::generate(shell, &mut command, name, &mut std::io::stdout());
+        return Ok(());
+    }
+    if let (Commands::Build { .. }, Some(_)) = (&cli.command, &cli.index) {
+        Cli::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                "build writes a new index, so pass --file or --url rather than --index",
+            )
+            .exit();
+    }
+    #[cfg(feature = "bundled")]
+    let builtin = (cli.builtin || std::io::stdin().is_terminal()).then_some(BUILTIN_HTML);
+    #[cfg(not(feature = "bundled"))]
+    let builtin: Option<&[u8]> = None;
+    if cli.file.is_none() && cli.url.is_none() && cli.index.is_none() && builtin.is_none() {
+        if matches!(cli.command, Commands::Repl) {
+            Cli::command()
+                .error(
+                    ErrorKind::MissingRequiredArgument,
+                    "the repl reads queries from stdin, so pass --file, --url or --index",
+                )
+                .exit();
+        }
+        if std::io::stdin().is_terminal() {
+            Cli::command()
+                .error(
+                    ErrorKind::MissingRequiredArgument,
+                    "no dictionary given: pass --file, --url or --index, or pipe HTML to stdin",
+                )
+                .exit();
+        }
+    }
 
     let (tx, mut rx) = oneshot::channel();
     let mut intv = interval(Duration::from_millis(500));
 
+    let cache = if cli.no_cache {
+        None
+    } else {
+        UrlCache::in_default_dir().map(|cache| match cli.cache_ttl {
+            Some(seconds) => cache.ttl(Duration::from_secs(seconds)),
+            None => cache,
+        })
+    };
+
+    let fetch_opts = FetchOptions {
+        timeout: Duration::from_secs(cli.timeout),
+        retries: cli.retries,
+        ..FetchOptions::default()
+    };
+
+    let build_out = match &cli.command {
+        Commands::Build { out } => Some(out.clone()),
+        _ => None,
+    };
+
     tokio::spawn(async move {
-        let dict = if let Some(url) = cli.url {
-            anglo_saxon_dict_parser::parse_url(url).await.expect("Couldn't parse HTML")
+        if let Some(index) = cli.index {
+            let dict = Dictionary::open(&index).expect("Couldn't open index");
+            let _ = tx.send(dict);
+            return;
+        }
+
+        let html = if let Some(url) = cli.url {
+            anglo_saxon_dict_parser::fetch_html(url, &fetch_opts, cache.as_ref())
+                .await
+                .expect("Couldn't download HTML")
         } else if let Some(file) = cli.file {
-            anglo_saxon_dict_parser::parse(&file).expect("Couldn't parse HTML")
+            anglo_saxon_dict_parser::read_html(&file).expect("Couldn't read HTML file")
+        } else if let Some(html) = builtin {
+            anglo_saxon_dict_parser::html_from_bytes(html.to_vec())
+                .expect("Couldn't read the built-in dictionary")
         } else {
-            todo!()
+            let mut html = String::new();
+            std::io::stdin()
+                .read_to_string(&mut html)
+                .expect("Couldn't read HTML from stdin");
+            html
+        };
+        let bar = progress_bar();
+        if cli.quiet {
+            bar.set_draw_target(ProgressDrawTarget::hidden());
+        }
+        let (mut entries, report) =
+            anglo_saxon_dict_parser::entries_from_html_with_progress(&html, |p| {
+                show_progress(&bar, p)
+            })
+            .expect("Couldn't parse HTML");
+        if let Some(source) = cli.source {
+            for entry in &mut entries {
+                entry.source = Some(source.clone());
+            }
+        }
+        if cli.verbose > 0 {
+            bar.suspend(|| print_report(&report));
+        }
+
+        let builder = Dictionary::builder();
+        let dict = if let Some(out) = build_out {
+            let count = entries.len();
+            let dict = builder
+                .in_dir(&out)
+                .build_with_progress(entries, |p| show_progress(&bar, p))
+                .expect("Couldn't write index");
+            bar.finish_and_clear();
+            println!("Indexed {} entries into {}", count, out.display());
+            dict
+        } else {
+            let dict = builder
+                .build_with_progress(entries, |p| show_progress(&bar, p))
+                .expect("Couldn't build index");
+            bar.finish_and_clear();
+            dict
         };
 
         let _ = tx.send(dict);
     });
 
+    let mut found_nothing = false;
     loop {
         tokio::select! {
             _ = intv.tick() => {
@@ -58,30 +468,754 @@ async fn main() -> anyhow::Result<()> {
             },
             result = &mut rx => {
                 if let Ok(dict) = result {
+                    info!("Loaded {} entries", dict.len());
+                    #[cfg(unix)]
+                    let _pager = if cli.pager
+                        && terminal().is_some()
+                        && matches!(
+                            cli.command,
+                            Commands::Search { .. } | Commands::Define { .. } | Commands::List { .. }
+                        ) {
+                        Some(pager::Pager::spawn()?)
+                    } else {
+                        None
+                    };
                     match &cli.command {
-                        Commands::Search { term } => {
-                            println!("{}: {}", "Search".bold().underline().blue(), term.bold());
-                            let results = dict.search(&term, None).expect("Couldn't search index");
-
-                            for result in results {
-                                println!("{} - {}", result.word.bold().blue(), result.definition);
-                            }
+                        Commands::Search {
+                            terms,
+                            terms_file,
+                            snippets: true,
+                            limit,
+                            sort,
+                            ..
+                        } => {
+                            let limit = Some(limit.unwrap_or(default_limit));
+                            let groups = search_terms(terms, terms_file.as_deref())?
+                                .into_iter()
+                                .map(|term| {
+                                    let mut results = dict.search_snippets(&term, limit)?;
+                                    match sort {
+                                        Sort::Relevance => {}
+                                        Sort::Alphabetical => results.sort_by(|(a, _), (b, _)| {
+                                            compare_old_english(&a.word, &b.word)
+                                        }),
+                                        Sort::Source => results.sort_by_key(|(entry, _)| {
+                                            (entry.ordinal.is_none(), entry.ordinal)
+                                        }),
+                                    }
+                                    Ok((term, results))
+                                })
+                                .collect::<anyhow::Result<Vec<_>>>()?;
+                            found_nothing = groups.iter().any(|(_, results)| results.is_empty());
+                            print_groups(cli.format, "Search", &groups)?;
                         }
-                        Commands::Define { term } => {
-                            println!("{}: {}", "Define".bold().underline().blue(), term.bold());
-                            let results = dict.define(&term).expect("Couldn't define term");
-
-                            for result in results {
-                                println!("{} - {}", result.word.bold().blue(), result.definition);
+                        Commands::Search {
+                            terms,
+                            terms_file,
+                            fuzzy,
+                            sort,
+                            pos,
+                            gloss,
+                            from,
+                            limit,
+                            ..
+                        } => {
+                            let limit = Some(limit.unwrap_or(default_limit));
+                            let groups = search_terms(terms, terms_file.as_deref())?
+                                .into_iter()
+                                .map(|term| {
+                                    let mut results = match fuzzy {
+                                        Some(distance) => dict.search_fuzzy(&term, *distance, limit),
+                                        None if *gloss => dict.search_definition(&term, limit),
+                                        None if from.is_some() => {
+                                            dict.search_with_source(&term, from.as_deref(), limit)
+                                        }
+                                        None => dict.search_with_pos(&term, pos.as_deref(), limit),
+                                    }?;
+                                    match sort {
+                                        Sort::Relevance => {}
+                                        Sort::Alphabetical => results
+                                            .sort_by(|a, b| compare_old_english(&a.word, &b.word)),
+                                        Sort::Source => sort_by_ordinal(&mut results),
+                                    }
+                                    Ok((term, results))
+                                })
+                                .collect::<anyhow::Result<Vec<_>>>()?;
+                            found_nothing = groups.iter().any(|(_, results)| results.is_empty());
+                            print_groups(cli.format, "Search", &groups)?;
+                        }
+                        Commands::Define {
+                            term,
+                            limit,
+                            fuzzy,
+                            case_sensitive,
+                            pos,
+                        } => {
+                            let term = &latin_query(term);
+                            let limit = Some(limit.unwrap_or(default_limit));
+                            let exact = |entries: Vec<Entry>| {
+                                entries.into_iter().map(|e| (MatchKind::Exact, e)).collect()
+                            };
+                            let results: Vec<_> = match fuzzy {
+                                Some(distance) => dict
+                                    .search_fuzzy(term, *distance, limit)?
+                                    .into_iter()
+                                    .map(|entry| {
+                                        let kind = if entry.word.to_lowercase() == term.to_lowercase()
+                                        {
+                                            MatchKind::Exact
+                                        } else {
+                                            MatchKind::Fuzzy
+                                        };
+                                        (kind, entry)
+                                    })
+                                    .collect(),
+                                None if *case_sensitive => exact(dict.define_exact(term, limit)?),
+                                None if pos.is_some() => {
+                                    exact(dict.define_with_pos(term, pos.as_deref(), limit)?)
+                                }
+                                None => dict.lookup(term, limit)?,
+                            };
+                            // Near misses alone don't count as finding the word
+                            found_nothing =
+                                !results.iter().any(|(kind, _)| *kind == MatchKind::Exact);
+                            print_groups(cli.format, "Define", &[(term, results.as_slice())])?;
+                            if found_nothing && fuzzy.is_none() {
+                                let suggestions = dict.did_you_mean(term, 3)?;
+                                print_did_you_mean(cli.format, &suggestions);
                             }
                         }
+                        Commands::List { prefix, contains } => {
+                            let results = if *contains {
+                                dict.words_containing(prefix)
+                            } else {
+                                dict.words_starting_with(prefix)
+                            }?;
+                            found_nothing = results.is_empty();
+                            print_entries(cli.format, "List", prefix, &results)?;
+                        }
+                        Commands::Random { seed } => {
+                            let entry = match seed {
+                                Some(seed) => dict.random_with_seed(*seed),
+                                None => dict.random(),
+                            }?;
+                            print_entries(cli.format, "Random", &entry.word.clone(), &[entry])?;
+                        }
+                        Commands::Suggest { prefix, limit } => {
+                            let words = dict.suggest(prefix, *limit)?;
+                            print_words(cli.format, &words)?;
+                        }
+                        Commands::Repl => repl(&dict, cli.format, default_limit)?,
+                        Commands::Build { .. } => {}
+                        Commands::Serve { addr } => {
+                            eprintln!("Listening on http://{}", addr);
+                            anglo_saxon_dict_parser::serve(dict, addr.as_str()).await?;
+                        }
+                        Commands::Export {
+                            format,
+                            out,
+                            normalize,
+                        } => export(&dict, *format, out.as_deref(), *normalize)?,
+                        Commands::Completions { .. } => unreachable!("handled before loading"),
                     }
                 } else {
-                    println!("Failed to load dictionary");
+                    eprintln!("Failed to load dictionary");
                 }
                 break;
             }
         }
     }
+    if cli.fail_on_empty && found_nothing {
+        std::io::stdout().flush()?;
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+#[derive(Clone, Copy)]
+enum Mode {
+    Search,
+    Define,
+}
+
+/// Answer queries read line by line from stdin until EOF or `:quit`.
+fn repl(dict: &Dictionary, format: Format, limit: usize) -> anyhow::Result<()> {
+    let mut mode = Mode::Search;
+    let mut line = String::new();
+    loop {
+        print!("{} ", ">".bold());
+        std::io::stdout().flush()?;
+        line.clear();
+        if std::io::stdin().lock().read_line(&mut line)? == 0 {
+            break;
+        }
+        let (command, rest) = match line.trim().split_once(char::is_whitespace) {
+            Some((command, rest)) => (command, rest.trim()),
+            None => (line.trim(), ""),
+        };
+        let (query_mode, query) = match command {
+            "" => continue,
+            ":quit" | ":q" => break,
+            "/def" | "/define" => (Mode::Define, rest),
+            "/search" => (Mode::Search, rest),
+            _ => (mode, line.trim()),
+        };
+        if query.is_empty() {
+            mode = query_mode;
+            continue;
+        }
+        let query = &latin_query(query);
+        let results = match query_mode {
+            Mode::Search => dict.search(query, Some(limit)),
+            Mode::Define => dict.define(query, Some(limit)),
+        };
+        match results {
+            Ok(results) => {
+                let title = match query_mode {
+                    Mode::Search => "Search",
+                    Mode::Define => "Define",
+                };
+                print_entries(format, title, query, &results)?;
+            }
+            Err(e) => eprintln!("{}: {:#}", "Error".bold().red(), anyhow::Error::from(e)),
+        }
+    }
+    Ok(())
+}
+
+/// Write every entry to stdout or `out` in the chosen export format.
+fn export(
+    dict: &Dictionary,
+    format: ExportFormat,
+    out: Option<&Path>,
+    normalize: bool,
+) -> anyhow::Result<()> {
+    match format {
+        ExportFormat::Jsonl => {
+            let mut out = std::io::BufWriter::new(std::io::stdout().lock());
+            dict.export_json_lines(&mut out)?;
+            out.flush()?;
+        }
+        ExportFormat::Json => {
+            let mut out = std::io::BufWriter::new(std::io::stdout().lock());
+            dict.export_json(&mut out)?;
+            writeln!(out)?;
+            out.flush()?;
+        }
+        ExportFormat::Wordlist => {
+            let mut out = std::io::BufWriter::new(std::io::stdout().lock());
+            dict.export_wordlist(&mut out, normalize)?;
+            out.flush()?;
+        }
+        ExportFormat::Stardict => {
+            let dir = out.expect("--out is required for StarDict");
+            dict.export_stardict(dir)?;
+            eprintln!("Wrote StarDict files to {}", dir.display());
+        }
+        ExportFormat::Sqlite => {
+            let path = out.expect("--out is required for SQLite");
+            dict.export_sqlite(path)?;
+            eprintln!("Wrote entries to {}", path.display());
+        }
+    }
+    Ok(())
+}
+
+/// Print the results of a command in the chosen format.
+fn print_entries(format: Format, title: &str, term: &str, entries: &[Entry]) -> anyhow::Result<()> {
+    print_groups(format, title, &[(term, entries)])
+}
+
+/// A search result that can be printed in each output format.
+trait Row: Sized {
+    const CSV_HEADER: &'static [&'static str];
+
+    /// Print the row as text, highlighting the parts of its headword that match `term`.
+    fn print_text(&self, term: &str);
+    /// The headword and definition shown in the two columns of table output.
+    fn table_cells(&self) -> (&str, &str);
+    fn to_json(&self) -> serde_json::Result<serde_json::Value>;
+    fn to_csv(&self) -> Vec<String>;
+}
+
+impl Row for Entry {
+    const CSV_HEADER: &'static [&'static str] =
+        &["word", "definition", "part_of_speech", "id", "see_also"];
+
+    fn print_text(&self, term: &str) {
+        println!(
+            "{} - {}",
+            highlight_word(&self.word, term),
+            shorten(&self.definition)
+        );
+        print_see_also(self);
+    }
+
+    fn table_cells(&self) -> (&str, &str) {
+        (&self.word, &self.definition)
+    }
+
+    fn to_json(&self) -> serde_json::Result<serde_json::Value> {
+        serde_json::to_value(self)
+    }
+
+    fn to_csv(&self) -> Vec<String> {
+        vec![
+            self.word.clone(),
+            self.definition.clone(),
+            self.part_of_speech.clone().unwrap_or_default(),
+            self.id.clone().unwrap_or_default(),
+            self.see_also.join(";"),
+        ]
+    }
+}
+
+impl Row for (Entry, Snippet) {
+    const CSV_HEADER: &'static [&'static str] =
+        &["word", "definition", "part_of_speech", "id", "snippet"];
+
+    fn print_text(&self, term: &str) {
+        let (entry, snippet) = self;
+        println!(
+            "{} - {}",
+            highlight_word(&entry.word, term),
+            highlight(entry, snippet)
+        );
+    }
+
+    fn table_cells(&self) -> (&str, &str) {
+        let (entry, snippet) = self;
+        if snippet.fragment.is_empty() {
+            (&entry.word, &entry.definition)
+        } else {
+            (&entry.word, &snippet.fragment)
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Result<serde_json::Value> {
+        let (entry, snippet) = self;
+        let mut value = serde_json::to_value(entry)?;
+        value["snippet"] = serde_json::to_value(snippet)?;
+        Ok(value)
+    }
+
+    fn to_csv(&self) -> Vec<String> {
+        let (entry, snippet) = self;
+        let mut record = entry.to_csv();
+        record.pop();
+        record.push(snippet.fragment.clone());
+        record
+    }
+}
+
+/// A `define` result, with close matches marked as such.
+impl Row for (MatchKind, Entry) {
+    const CSV_HEADER: &'static [&'static str] = &[
+        "word",
+        "definition",
+        "part_of_speech",
+        "id",
+        "see_also",
+        "match",
+    ];
+
+    fn print_text(&self, term: &str) {
+        let (kind, entry) = self;
+        if *kind == MatchKind::Fuzzy {
+            print!("{} ", "~".italic());
+        }
+        entry.print_text(term);
+    }
+
+    fn table_cells(&self) -> (&str, &str) {
+        self.1.table_cells()
+    }
+
+    fn to_json(&self) -> serde_json::Result<serde_json::Value> {
+        let (kind, entry) = self;
+        let mut value = serde_json::to_value(entry)?;
+        value["match"] = serde_json::to_value(kind)?;
+        Ok(value)
+    }
+
+    fn to_csv(&self) -> Vec<String> {
+        let (kind, entry) = self;
+        let mut record = entry.to_csv();
+        let kind = match kind {
+            MatchKind::Exact => "exact",
+            MatchKind::Fuzzy => "fuzzy",
+        };
+        record.push(kind.to_owned());
+        record
+    }
+}
+
+/// Print the results for each of several terms. A single term's results are printed as they
+/// always were; with more, JSON output is an array of `{"term", "results"}` objects and CSV
+/// output gains a leading `term` column.
+fn print_groups<T: AsRef<str>, R: Row, G: AsRef<[R]>>(
+    format: Format,
+    title: &str,
+    groups: &[(T, G)],
+) -> anyhow::Result<()> {
+    let single = groups.len() == 1;
+    match format {
+        Format::Text | Format::Table => {
+            let table = matches!(format, Format::Table) && terminal().is_some();
+            for (i, (term, rows)) in groups.iter().enumerate() {
+                if i > 0 {
+                    println!();
+                }
+                println!(
+                    "{}: {}",
+                    title.bold().underline().blue(),
+                    term.as_ref().bold()
+                );
+                if rows.as_ref().is_empty() {
+                    println!("No results for '{}'.", term.as_ref());
+                } else if table {
+                    println!("{}", results_table(rows.as_ref()));
+                } else {
+                    for row in rows.as_ref() {
+                        row.print_text(term.as_ref());
+                    }
+                }
+            }
+        }
+        Format::Json => {
+            let groups = groups
+                .iter()
+                .map(|(term, rows)| {
+                    let results = rows
+                        .as_ref()
+                        .iter()
+                        .map(Row::to_json)
+                        .collect::<serde_json::Result<Vec<_>>>()?;
+                    Ok((term.as_ref(), results))
+                })
+                .collect::<serde_json::Result<Vec<_>>>()?;
+            let value = if single {
+                serde_json::Value::from(groups.into_iter().next().unwrap_or_default().1)
+            } else {
+                groups
+                    .into_iter()
+                    .map(|(term, results)| serde_json::json!({ "term": term, "results": results }))
+                    .collect()
+            };
+            println!("{}", serde_json::to_string_pretty(&value)?);
+        }
+        Format::Csv => {
+            let mut writer = csv::Writer::from_writer(std::io::stdout());
+            let term_column = if single { None } else { Some("term") };
+            writer.write_record(term_column.iter().chain(R::CSV_HEADER))?;
+            for (term, rows) in groups {
+                for row in rows.as_ref() {
+                    let term = (!single).then(|| term.as_ref().to_owned());
+                    writer.write_record(term.into_iter().chain(row.to_csv()))?;
+                }
+            }
+            writer.flush()?;
+        }
+    }
     Ok(())
 }
+
+/// The terms given on the command line followed by the non-blank lines of `terms_file`.
+fn search_terms(terms: &[String], terms_file: Option<&Path>) -> anyhow::Result<Vec<String>> {
+    let mut terms = terms.to_vec();
+    if let Some(path) = terms_file {
+        let file = std::fs::read_to_string(path)
+            .with_context(|| format!("Couldn't read terms from {}", path.display()))?;
+        terms.extend(
+            file.lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(str::to_owned),
+        );
+    }
+    if terms.is_empty() {
+        anyhow::bail!("No terms to search for");
+    }
+    Ok(terms.iter().map(|term| latin_query(term)).collect())
+}
+
+/// Transliterate a query written in futhorc runes, leaving Latin queries untouched.
+fn latin_query(term: &str) -> String {
+    if is_runic(term) {
+        transliterate_futhorc(term)
+    } else {
+        term.to_owned()
+    }
+}
+
+/// Log to stderr at the level the -v and -q flags ask for, or as RUST_LOG says without them.
+/// Other crates' messages stay at warnings and above so tantivy's don't drown out ours.
+fn init_logging(verbose: u8, quiet: bool) {
+    let level = match (quiet, verbose) {
+        (true, _) => Some(LevelFilter::Off),
+        (false, 0) => None,
+        (false, 1) => Some(LevelFilter::Info),
+        (false, 2) => Some(LevelFilter::Debug),
+        (false, _) => Some(LevelFilter::Trace),
+    };
+    let mut builder = env_logger::Builder::from_default_env();
+    if let Some(level) = level {
+        builder
+            .filter_level(level.min(LevelFilter::Warn))
+            .filter_module("anglo_saxon_dict_parser", level)
+            .filter_module("anglo_saxon_dictionary", level);
+    }
+    builder.init();
+}
+
+/// A bar on stderr for parsing and indexing progress, hidden when stderr isn't a terminal.
+fn progress_bar() -> ProgressBar {
+    let bar = ProgressBar::new(0);
+    bar.set_style(
+        ProgressStyle::with_template("{msg:>8} [{bar:40}] {pos}/{len}")
+            .expect("Progress bar template is valid")
+            .progress_chars("=> "),
+    );
+    bar
+}
+
+fn show_progress(bar: &ProgressBar, progress: Progress) {
+    let (message, done, total) = match progress {
+        Progress::Parsed { done, total } => ("Parsing", done, total),
+        Progress::Indexed { done, total } => ("Indexing", done, total),
+    };
+    if bar.message() != message {
+        bar.reset();
+        bar.set_length(total as u64);
+        bar.set_message(message);
+    }
+    bar.set_position(done as u64);
+}
+
+/// Summarize what parsing produced on stderr, listing each skipped paragraph.
+fn print_report(report: &ParseReport) {
+    eprintln!(
+        "Parsed {} entries from {} paragraphs, skipping {}",
+        report.entries, report.total_paragraphs, report.skipped
+    );
+    for warning in &report.warnings {
+        eprintln!("  skipped: {}", warning);
+    }
+}
+
+/// Suggest alternatives for a word with no definition. Structured formats keep stdout for
+/// results, so the suggestions go to stderr.
+fn print_did_you_mean(format: Format, suggestions: &[String]) {
+    if suggestions.is_empty() {
+        return;
+    }
+    let message = format!("Did you mean: {}?", suggestions.join(", "));
+    match format {
+        Format::Text | Format::Table => println!("{}", message),
+        Format::Json | Format::Csv => eprintln!("{}", message),
+    }
+}
+
+fn print_words(format: Format, words: &[String]) -> anyhow::Result<()> {
+    match format {
+        Format::Text | Format::Table => {
+            for word in words {
+                println!("{}", word);
+            }
+        }
+        Format::Json => println!("{}", serde_json::to_string_pretty(words)?),
+        Format::Csv => {
+            let mut writer = csv::Writer::from_writer(std::io::stdout());
+            writer.write_record(["word"])?;
+            for word in words {
+                writer.write_record([word])?;
+            }
+            writer.flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// The rows as a word | definition table, with the headwords colored when colors are on.
+fn results_table<R: Row>(rows: &[R]) -> Table {
+    let color = colored::control::SHOULD_COLORIZE.should_colorize();
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL_CONDENSED)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(["Word", "Definition"]);
+    // Stdout may be a pager by now, so don't let the table check it
+    if let Some(Some(width)) = terminal() {
+        table.set_width(width);
+    }
+    if color {
+        table.enforce_styling();
+    }
+    for row in rows {
+        let (word, definition) = row.table_cells();
+        let mut word = Cell::new(word);
+        if color {
+            word = word.fg(Color::Blue).add_attribute(Attribute::Bold);
+        }
+        table.add_row([word, Cell::new(shorten(definition))]);
+    }
+    table
+}
+
+/// `definition` cut to `--max-def-len` characters, if it's longer.
+fn shorten(definition: &str) -> Cow<'_, str> {
+    match MAX_DEFINITION_LEN.get() {
+        Some(&max) => truncate(definition, max),
+        None => Cow::Borrowed(definition),
+    }
+}
+
+/// `text` cut to its first `max` characters with "…" after them, if it's longer.
+fn truncate(text: &str, max: usize) -> Cow<'_, str> {
+    match text.char_indices().nth(max) {
+        Some((end, _)) => Cow::Owned(format!("{}…", text[..end].trim_end())),
+        None => Cow::Borrowed(text),
+    }
+}
+
+fn print_see_also(entry: &Entry) {
+    if !entry.see_also.is_empty() {
+        println!("    {} {}", "See also:".italic(), entry.see_also.join(", "));
+    }
+}
+
+/// The headword with the parts matching `term` underlined.
+fn highlight_word(word: &str, term: &str) -> String {
+    let mut highlighted = String::new();
+    let mut end = 0;
+    for range in word_matches(word, term) {
+        highlighted.push_str(&word[end..range.start].bold().blue().to_string());
+        highlighted.push_str(&word[range.clone()].bold().blue().underline().to_string());
+        end = range.end;
+    }
+    highlighted.push_str(&word[end..].bold().blue().to_string());
+    highlighted
+}
+
+/// The byte ranges of `word` that match any word of `term`, ignoring case, merged where they
+/// overlap and in order.
+fn word_matches(word: &str, term: &str) -> Vec<Range<usize>> {
+    let chars = word.char_indices().collect::<Vec<_>>();
+    let mut ranges = Vec::new();
+    for needle in term
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|needle| !needle.is_empty())
+    {
+        let needle = needle.chars().collect::<Vec<_>>();
+        for (start, window) in chars.windows(needle.len()).enumerate() {
+            let matched = window
+                .iter()
+                .zip(&needle)
+                .all(|((_, a), b)| a.to_lowercase().eq(b.to_lowercase()));
+            if matched {
+                let end = chars
+                    .get(start + needle.len())
+                    .map_or(word.len(), |(i, _)| *i);
+                ranges.push(chars[start].0..end);
+            }
+        }
+    }
+    ranges.sort_by_key(|range| range.start);
+    let mut merged: Vec<Range<usize>> = Vec::new();
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+/// The snippet with its matches colored, or the whole definition when only the headword
+/// matched.
+fn highlight(entry: &Entry, snippet: &Snippet) -> String {
+    if snippet.fragment.is_empty() {
+        return shorten(&entry.definition).into_owned();
+    }
+    let mut highlighted = String::new();
+    let mut end = 0;
+    for range in &snippet.highlighted {
+        highlighted.push_str(&snippet.fragment[end..range.start]);
+        highlighted.push_str(&snippet.fragment[range.clone()].bold().yellow().to_string());
+        end = range.end;
+    }
+    highlighted.push_str(&snippet.fragment[end..]);
+    highlighted
+}
+
+#[cfg(test)]
+mod test {
+    use super::{truncate, word_matches, Cli};
+    use clap::error::ErrorKind;
+    use clap::{CommandFactory, Parser};
+
+    #[test]
+    fn test_cli() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn test_sources_conflict() {
+        let error = Cli::try_parse_from([
+            "anglo-saxon-dictionary",
+            "--file",
+            "dictionary.html",
+            "--url",
+            "https://example.com/dictionary.html",
+            "define",
+            "cyning",
+        ])
+        .err()
+        .unwrap();
+        assert_eq!(ErrorKind::ArgumentConflict, error.kind());
+
+        let error = Cli::try_parse_from([
+            "anglo-saxon-dictionary",
+            "--index",
+            "index",
+            "--file",
+            "dictionary.html",
+            "define",
+            "cyning",
+        ])
+        .err()
+        .unwrap();
+        assert_eq!(ErrorKind::ArgumentConflict, error.kind());
+
+        for source in [["--file", "dictionary.html"], ["--index", "index"]] {
+            let args = [
+                "anglo-saxon-dictionary",
+                source[0],
+                source[1],
+                "define",
+                "cyning",
+            ];
+            assert!(Cli::try_parse_from(args).is_ok());
+        }
+        // With no source, HTML is read from stdin
+        assert!(Cli::try_parse_from(["anglo-saxon-dictionary", "define", "cyning"]).is_ok());
+    }
+
+    #[test]
+    fn test_word_matches() {
+        assert_eq!(vec![0..3], word_matches("cyning", "CYN"));
+        assert_eq!(vec![2..6], word_matches("Æþeling", "þel"));
+        assert_eq!(vec![0..2, 3..6], word_matches("cyning", "cy ing"));
+        assert_eq!(vec![1..6], word_matches("cyning", "yni ning"));
+        assert!(word_matches("cyning", "leoht").is_empty());
+        assert!(word_matches("cyning", "*").is_empty());
+    }
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!("king", truncate("king", 4));
+        assert_eq!("king,…", truncate("king, ruler", 6));
+        assert_eq!("æþel…", truncate("æþeling", 4));
+        assert_eq!("…", truncate("king", 0));
+    }
+}
@@ -0,0 +1,54 @@
+use anyhow::Context;
+use std::io::Write;
+use std::os::fd::AsRawFd;
+use std::process::{Child, Command, Stdio};
+
+/// A pager reading everything printed to stdout. Dropping it hands stdout back to the
+/// terminal and waits for the reader to quit the pager.
+pub struct Pager {
+    child: Child,
+    stdout: libc::c_int,
+}
+
+impl Pager {
+    /// Start `$PAGER`, or `less` when it isn't set, and send stdout to it.
+    pub fn spawn() -> anyhow::Result<Self> {
+        let pager = std::env::var("PAGER")
+            .ok()
+            .filter(|pager| !pager.trim().is_empty())
+            .unwrap_or_else(|| "less".to_owned());
+        let mut command = Command::new("sh");
+        command.arg("-c").arg(&pager).stdin(Stdio::piped());
+        // Have less keep colors, and quit straight away when everything fits on one screen
+        if std::env::var_os("LESS").is_none() {
+            command.env("LESS", "FRX");
+        }
+        let mut child = command
+            .spawn()
+            .with_context(|| format!("Couldn't start pager {:?}", pager))?;
+        let input = child.stdin.take().expect("Pager stdin is piped");
+
+        std::io::stdout().flush()?;
+        // SAFETY: these only duplicate descriptors this process owns
+        let stdout = unsafe { libc::dup(libc::STDOUT_FILENO) };
+        if stdout == -1 || unsafe { libc::dup2(input.as_raw_fd(), libc::STDOUT_FILENO) } == -1 {
+            return Err(std::io::Error::last_os_error())
+                .context("Couldn't send output to the pager");
+        }
+        Ok(Pager { child, stdout })
+    }
+}
+
+impl Drop for Pager {
+    fn drop(&mut self) {
+        let _ = std::io::stdout().flush();
+        // Putting stdout back closes the last copy of the pipe, so the pager sees the end of
+        // the output
+        // SAFETY: `self.stdout` is the descriptor duplicated in `spawn`, closed only here
+        unsafe {
+            libc::dup2(self.stdout, libc::STDOUT_FILENO);
+            libc::close(self.stdout);
+        }
+        let _ = self.child.wait();
+    }
+}
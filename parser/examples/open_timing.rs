@@ -0,0 +1,56 @@
+//! Compare building a dictionary in memory with opening a memory-mapped index built from the
+//! same file:
+//!
+//! ```sh
+//! cargo run --release --example open_timing -- pg31543-images.html
+//! ```
+//!
+//! Resident memory is read from `/proc/self/status`, so it's only reported on Linux.
+
+use anglo_saxon_dict_parser::{entries_from_html, Dictionary};
+use std::time::Instant;
+
+fn main() -> anglo_saxon_dict_parser::Result<()> {
+    let path = std::env::args()
+        .nth(1)
+        .expect("Usage: open_timing <dictionary.html>");
+    let html = std::fs::read_to_string(&path).expect("Couldn't read the dictionary HTML");
+    let entries = entries_from_html(&html)?;
+    let dir = tempfile::tempdir().expect("Couldn't create a temporary directory");
+    drop(Dictionary::create_in_dir(dir.path(), entries.clone())?);
+
+    let before = resident_kb();
+    let start = Instant::now();
+    let in_ram = Dictionary::new(entries)?;
+    report("In RAM", start, before);
+
+    let before = resident_kb();
+    let start = Instant::now();
+    let mmap = Dictionary::open(dir.path())?;
+    report("Memory-mapped", start, before);
+
+    drop((in_ram, mmap));
+    Ok(())
+}
+
+fn report(label: &str, start: Instant, before: Option<u64>) {
+    let elapsed = start.elapsed();
+    match (before, resident_kb()) {
+        (Some(before), Some(after)) => println!(
+            "{label}: ready in {elapsed:?}, resident memory +{} kB",
+            after.saturating_sub(before)
+        ),
+        _ => println!("{label}: ready in {elapsed:?}"),
+    }
+}
+
+fn resident_kb() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmRSS:"))?
+        .trim()
+        .trim_end_matches(" kB")
+        .parse()
+        .ok()
+}
@@ -0,0 +1,19 @@
+//! Time parsing and indexing a dictionary HTML file, e.g. to compare the `rayon` feature:
+//!
+//! ```sh
+//! cargo run --release --example parse_timing -- pg31543-images.html
+//! cargo run --release --example parse_timing --features rayon -- pg31543-images.html
+//! ```
+
+use std::time::Instant;
+
+fn main() -> anglo_saxon_dict_parser::Result<()> {
+    let path = std::env::args()
+        .nth(1)
+        .expect("Usage: parse_timing <dictionary.html>");
+
+    let start = Instant::now();
+    anglo_saxon_dict_parser::parse(&path)?;
+    println!("Parsed and indexed {} in {:?}", path, start.elapsed());
+    Ok(())
+}
@@ -0,0 +1,34 @@
+//! Time repeated searches against a dictionary HTML file:
+//!
+//! ```sh
+//! cargo run --release --example query_timing -- pg31543-images.html [ITERATIONS]
+//! ```
+
+use std::time::Instant;
+
+const QUERIES: [&str; 4] = ["king", "light", "stone OR rock", "definition:\"a god\""];
+
+fn main() -> anglo_saxon_dict_parser::Result<()> {
+    let mut args = std::env::args().skip(1);
+    let path = args
+        .next()
+        .expect("Usage: query_timing <dictionary.html> [ITERATIONS]");
+    let iterations: usize = args
+        .next()
+        .map(|n| n.parse().expect("ITERATIONS must be a number"))
+        .unwrap_or(10_000);
+    let dictionary = anglo_saxon_dict_parser::parse(&path)?;
+
+    let start = Instant::now();
+    for i in 0..iterations {
+        dictionary.search(QUERIES[i % QUERIES.len()], None)?;
+    }
+    let elapsed = start.elapsed();
+    println!(
+        "{} searches in {:?}, {:.0} per second",
+        iterations,
+        elapsed,
+        iterations as f64 / elapsed.as_secs_f64()
+    );
+    Ok(())
+}
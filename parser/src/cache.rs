@@ -0,0 +1,95 @@
+use crate::{DictionaryError, Result};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// The default directory for cached downloads: `$XDG_CACHE_HOME/anglo-saxon-dictionary`, or
+/// `~/.cache/anglo-saxon-dictionary` when `XDG_CACHE_HOME` isn't set.
+pub fn default_cache_dir() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))?;
+    Some(base.join("anglo-saxon-dictionary"))
+}
+
+/// A directory of downloaded dictionary HTML, keyed by a hash of the URL.
+#[derive(Debug, Clone)]
+pub struct UrlCache {
+    dir: PathBuf,
+    ttl: Option<Duration>,
+}
+
+impl UrlCache {
+    /// Cache downloads in the given directory. Cached pages never expire unless a TTL is set.
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        UrlCache {
+            dir: dir.into(),
+            ttl: None,
+        }
+    }
+
+    /// Cache downloads in [`default_cache_dir`].
+    pub fn in_default_dir() -> Option<Self> {
+        default_cache_dir().map(UrlCache::new)
+    }
+
+    /// Refetch pages cached longer ago than `ttl`.
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The file the page at `url` is cached in.
+    pub fn path(&self, url: &str) -> PathBuf {
+        self.dir
+            .join(format!("{:016x}.html", fnv1a(url.as_bytes())))
+    }
+
+    /// The cached page for `url`, if there is one that hasn't expired.
+    pub fn get(&self, url: &str) -> Result<Option<String>> {
+        let path = self.path(url);
+        let modified = match std::fs::metadata(&path).and_then(|m| m.modified()) {
+            Ok(modified) => modified,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => {
+                return Err(DictionaryError::io(
+                    format!("Couldn't read {}", path.display()),
+                    e,
+                ))
+            }
+        };
+        let age = SystemTime::now()
+            .duration_since(modified)
+            .unwrap_or_default();
+        if self.ttl.is_some_and(|ttl| age > ttl) {
+            return Ok(None);
+        }
+        std::fs::read_to_string(&path)
+            .map(Some)
+            .map_err(|e| DictionaryError::io(format!("Couldn't read {}", path.display()), e))
+    }
+
+    /// Store the page fetched from `url`.
+    pub fn put(&self, url: &str, html: &str) -> Result<()> {
+        std::fs::create_dir_all(&self.dir).map_err(|e| {
+            DictionaryError::io(format!("Couldn't create {}", self.dir.display()), e)
+        })?;
+        let path = self.path(url);
+        // Write then rename so a reader never sees a partial page
+        let partial = path.with_extension("html.partial");
+        std::fs::write(&partial, html)
+            .and_then(|_| std::fs::rename(&partial, &path))
+            .map_err(|e| DictionaryError::io(format!("Couldn't write {}", path.display()), e))
+    }
+}
+
+/// 64-bit FNV-1a, which is stable across Rust versions unlike `DefaultHasher`.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+    })
+}
@@ -0,0 +1,63 @@
+//! Transcoding dictionary HTML saved in legacy encodings, such as the Latin-1 of older
+//! editions, which writes þ, ð and æ as single bytes.
+
+use encoding_rs::{Encoding, UTF_8};
+use log::warn;
+use std::borrow::Cow;
+
+/// How far into a document its `<meta>` charset declaration is looked for, as browsers do.
+const PRESCAN_LEN: usize = 1024;
+
+/// Decode HTML bytes to text. UTF-8 is used as is. Anything else is decoded with the
+/// encoding its byte order mark or `<meta>` tag declares, or failing that as UTF-8 with the
+/// invalid bytes replaced, logging a warning naming `source` either way it had to guess.
+pub(crate) fn decode<'a>(bytes: Cow<'a, [u8]>, source: &str) -> Cow<'a, str> {
+    let bytes = match bytes {
+        Cow::Borrowed(bytes) => match std::str::from_utf8(bytes) {
+            Ok(text) => return Cow::Borrowed(text),
+            Err(_) => Cow::Borrowed(bytes),
+        },
+        Cow::Owned(bytes) => match String::from_utf8(bytes) {
+            Ok(text) => return Cow::Owned(text),
+            Err(e) => Cow::Owned(e.into_bytes()),
+        },
+    };
+    let declared = Encoding::for_bom(&bytes)
+        .map(|(encoding, _)| encoding)
+        .or_else(|| declared_encoding(&bytes));
+    let encoding = match declared {
+        Some(encoding) => encoding,
+        None => {
+            warn!(
+                "{} isn't UTF-8 and doesn't declare its encoding; replacing invalid bytes",
+                source
+            );
+            UTF_8
+        }
+    };
+    let (text, _, had_errors) = encoding.decode(&bytes);
+    if had_errors && declared.is_some() {
+        warn!(
+            "{} has bytes that aren't valid {}; replacing them",
+            source,
+            encoding.name()
+        );
+    }
+    Cow::Owned(text.into_owned())
+}
+
+/// The encoding named by a `<meta charset="...">` tag, or by the `charset` parameter of a
+/// `<meta http-equiv="Content-Type" content="...">` tag, near the start of `bytes`.
+fn declared_encoding(bytes: &[u8]) -> Option<&'static Encoding> {
+    let head = String::from_utf8_lossy(&bytes[..bytes.len().min(PRESCAN_LEN)]).to_ascii_lowercase();
+    head.split("<meta").skip(1).find_map(|tag| {
+        let tag = &tag[..tag.find('>').unwrap_or(tag.len())];
+        let rest = &tag[tag.find("charset")? + "charset".len()..];
+        let rest = rest.trim_start().strip_prefix('=')?.trim_start();
+        let label = rest
+            .trim_start_matches(['"', '\''])
+            .split(|c: char| c.is_whitespace() || matches!(c, '"' | '\'' | ';' | '/'))
+            .next()?;
+        Encoding::for_label(label.as_bytes())
+    })
+}
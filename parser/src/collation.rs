@@ -0,0 +1,54 @@
+use std::cmp::Ordering;
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+/// The order results are returned in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SortOrder {
+    /// Best match first, by BM25 score.
+    #[default]
+    Relevance,
+    /// By headword, in Old English alphabetical order. See [`compare_old_english`].
+    AlphabeticalOE,
+    /// In the order entries appeared in the source document. See `Entry::ordinal`.
+    Source,
+}
+
+/// Compare two words in the alphabetical order of the Old English dictionaries:
+///
+/// ```text
+/// a (æ) b c d e f g h i l m n o p r s t þ (ð) u w (ƿ) x y
+/// ```
+///
+/// * æ is alphabetized as "ae", so it follows "ad" and precedes "af".
+/// * þ and ð are the same letter, following t.
+/// * ƿ (wynn) is w.
+/// * Case, macrons and other diacritics and hyphens are ignored, except to break ties between
+///   otherwise equal words. Letters outside the Old English alphabet (k, q, v, z) take their
+///   modern places.
+pub fn compare_old_english(a: &str, b: &str) -> Ordering {
+    collation_key(a)
+        .cmp(&collation_key(b))
+        .then_with(|| a.cmp(b))
+}
+
+/// The weight of each letter for sorting; þ slots in between t and u.
+fn collation_key(word: &str) -> Vec<u32> {
+    let mut key = Vec::with_capacity(word.len());
+    for c in word.nfd().filter(|c| !is_combining_mark(*c)) {
+        match c.to_lowercase().next().unwrap_or(c) {
+            'æ' => key.extend([weight('a'), weight('e')]),
+            'þ' | 'ð' => key.push(weight('t') + 1),
+            'ƿ' => key.push(weight('w')),
+            c @ 'a'..='z' => key.push(weight(c)),
+            c if c.is_alphanumeric() => key.push(0x1000 + c as u32),
+            _ => {}
+        }
+    }
+    key
+}
+
+fn weight(letter: char) -> u32 {
+    (letter as u32 - 'a' as u32) * 2
+}
@@ -0,0 +1,161 @@
+//! Explaining what parsing made of the paragraphs mentioning one word, for tracking down
+//! why it's missing from a dictionary or defined wrongly.
+
+use crate::{continuations, fold, headword_key, is_entry_paragraph, Entry, ParseOptions};
+use ego_tree::NodeId;
+use log::debug;
+use scraper::{ElementRef, Html, Selector};
+use std::collections::HashSet;
+use std::fmt;
+
+/// What parsing made of every paragraph of a document that mentions a word.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WordDiagnostic {
+    pub word: String,
+    /// The paragraphs mentioning the word, in document order. A paragraph mentions it when
+    /// one of its words folds, as by [`fold`], to the same letters.
+    pub paragraphs: Vec<ParagraphDiagnostic>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParagraphDiagnostic {
+    /// The paragraph's HTML, as parsed.
+    pub html: String,
+    /// The `word_` id of the paragraph's anchor, if it has one.
+    pub id: Option<String>,
+    pub outcome: ParagraphOutcome,
+}
+
+/// Why a paragraph did or didn't become entries.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParagraphOutcome {
+    /// The paragraph was converted into these entries, which may have other headwords
+    /// when it only mentions the word in a definition.
+    Converted(Vec<Entry>),
+    /// The paragraph looked like an entry but was skipped, for this reason.
+    Skipped(String),
+    /// The paragraph continues the definition of the entry paragraph before it.
+    Continuation,
+    /// The paragraph isn't an entry: its first child isn't an anchor with a `word_` id.
+    NotAnEntry,
+}
+
+impl WordDiagnostic {
+    /// The entries whose headword is the word, as `Dictionary::define` would match it.
+    pub fn entries(&self) -> impl Iterator<Item = &Entry> {
+        let key = headword_key(&self.word);
+        self.paragraphs
+            .iter()
+            .filter_map(|paragraph| match &paragraph.outcome {
+                ParagraphOutcome::Converted(entries) => Some(entries),
+                _ => None,
+            })
+            .flatten()
+            .filter(move |entry| headword_key(&entry.word) == key)
+    }
+}
+
+/// Parse `html` as `parse_str` would, recording the outcome for each paragraph mentioning
+/// `word`.
+pub(crate) fn diagnose(html: &str, word: &str, options: &ParseOptions) -> WordDiagnostic {
+    let document = Html::parse_document(html);
+    let selector = Selector::parse("p").expect("Valid selector");
+    let continued: HashSet<NodeId> = document
+        .select(&selector)
+        .filter(is_entry_paragraph)
+        .flat_map(continuations)
+        .map(|paragraph| paragraph.id())
+        .collect();
+    let folded = fold(word).to_lowercase();
+    let paragraphs = document
+        .select(&selector)
+        .filter(|paragraph| mentions(paragraph, &folded))
+        .map(|paragraph| {
+            let id = paragraph
+                .first_child()
+                .and_then(|child| child.value().as_element())
+                .and_then(|el| el.attr("id"))
+                .filter(|id| id.starts_with("word_"))
+                .map(str::to_owned);
+            let outcome = if is_entry_paragraph(&paragraph) {
+                match Entry::from_paragraph_with_options(paragraph, options) {
+                    Ok(entries) => ParagraphOutcome::Converted(entries),
+                    Err(e) => ParagraphOutcome::Skipped(e.to_string()),
+                }
+            } else if continued.contains(&paragraph.id()) {
+                ParagraphOutcome::Continuation
+            } else {
+                ParagraphOutcome::NotAnEntry
+            };
+            debug!("{:?} in paragraph {:?}: {:?}", word, id, outcome);
+            ParagraphDiagnostic {
+                html: paragraph.html(),
+                id,
+                outcome,
+            }
+        })
+        .collect();
+    WordDiagnostic {
+        word: word.to_owned(),
+        paragraphs,
+    }
+}
+
+/// Whether one of the words of `paragraph` folds to `folded`.
+fn mentions(paragraph: &ElementRef, folded: &str) -> bool {
+    let text = fold(&paragraph.text().collect::<String>()).to_lowercase();
+    text.split(|c: char| !c.is_alphanumeric())
+        .any(|token| token == folded)
+}
+
+/// A report for reading in a terminal: each paragraph's id and outcome, with the entries it
+/// produced or, when it produced none, its HTML.
+impl fmt::Display for WordDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let key = headword_key(&self.word);
+        let found = self
+            .paragraphs
+            .iter()
+            .filter(|paragraph| match &paragraph.outcome {
+                ParagraphOutcome::Converted(entries) => {
+                    entries.iter().any(|entry| headword_key(&entry.word) == key)
+                }
+                _ => false,
+            })
+            .count();
+        writeln!(
+            f,
+            "{:?}: defined in {} of the {} paragraphs mentioning it",
+            self.word,
+            found,
+            self.paragraphs.len()
+        )?;
+        for paragraph in &self.paragraphs {
+            let id = paragraph.id.as_deref().unwrap_or("without an id");
+            match &paragraph.outcome {
+                ParagraphOutcome::Converted(entries) => {
+                    writeln!(f, "Paragraph {}: converted to", id)?;
+                    for entry in entries {
+                        writeln!(f, "  {}", entry)?;
+                    }
+                    continue;
+                }
+                ParagraphOutcome::Skipped(reason) => {
+                    writeln!(f, "Paragraph {}: skipped: {}", id, reason)?
+                }
+                ParagraphOutcome::Continuation => writeln!(
+                    f,
+                    "Paragraph {}: continues the definition of the entry before it",
+                    id
+                )?,
+                ParagraphOutcome::NotAnEntry => writeln!(
+                    f,
+                    "Paragraph {}: not an entry, as its first child isn't a word_ anchor",
+                    id
+                )?,
+            }
+            writeln!(f, "  {}", paragraph.html)?;
+        }
+        Ok(())
+    }
+}
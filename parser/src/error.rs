@@ -0,0 +1,103 @@
+use std::path::PathBuf;
+use tantivy::query::QueryParserError;
+use tantivy::TantivyError;
+
+/// Errors raised while parsing dictionary HTML, building its index, or querying it.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum DictionaryError {
+    /// The dictionary HTML or index directory couldn't be read or written
+    #[error("{context}")]
+    Io {
+        context: String,
+        #[source]
+        source: std::io::Error,
+    },
+    /// The dictionary HTML couldn't be downloaded
+    #[cfg(any(feature = "fetch", feature = "blocking"))]
+    #[error("Couldn't fetch dictionary HTML")]
+    Fetch(#[from] reqwest::Error),
+    /// A paragraph couldn't be converted into entries
+    #[error("{0}")]
+    Parse(String),
+    /// A CSS selector for choosing entries or headwords couldn't be parsed
+    #[error("Invalid selector {0:?}")]
+    InvalidSelector(String),
+    /// A line of JSON Lines input isn't a valid entry
+    #[cfg(feature = "serde")]
+    #[error("Invalid entry on line {line}")]
+    Json {
+        line: usize,
+        #[source]
+        source: serde_json::Error,
+    },
+    /// JSON input was exported in a format version this crate doesn't read
+    #[cfg(feature = "serde")]
+    #[error(
+        "Export format version {0} isn't supported; expected version {expected}",
+        expected = crate::EXPORT_FORMAT_VERSION
+    )]
+    IncompatibleExport(u32),
+    /// `DictionaryOptions::writer_heap` is outside the range tantivy accepts
+    #[error(
+        "Writer heap of {0} bytes is outside the accepted range of {min} to {max} bytes",
+        min = crate::MIN_WRITER_HEAP,
+        max = crate::MAX_WRITER_HEAP
+    )]
+    InvalidWriterHeap(usize),
+    /// The SQLite database couldn't be created or written to
+    #[cfg(feature = "sqlite")]
+    #[error("SQLite export failed")]
+    Sqlite(#[from] rusqlite::Error),
+    /// The index couldn't be created, opened or written to
+    #[error("{context}")]
+    Index {
+        context: String,
+        #[source]
+        source: TantivyError,
+    },
+    /// The directory doesn't hold an index
+    #[error("No index found in {}", .0.display())]
+    NoIndex(PathBuf),
+    /// The index was built by an incompatible version of this crate
+    #[error("Index in {} was built with an incompatible schema", .0.display())]
+    IncompatibleSchema(PathBuf),
+    /// The query string isn't valid query parser syntax. `message` says what's wrong, e.g.
+    /// "unknown field 'defnition', did you mean 'definition'?"
+    #[error("Invalid query: {message}")]
+    QueryParse {
+        message: String,
+        #[source]
+        source: QueryParserError,
+    },
+    /// The query was rejected for another reason
+    #[error("Invalid query: {0}")]
+    InvalidQuery(String),
+    /// No entry with the given headword matches the query being explained
+    #[error("No entry for {0:?} matches the query")]
+    NoMatch(String),
+    /// The dictionary has no entries to choose from
+    #[error("The dictionary is empty")]
+    Empty,
+    /// Running a query against the index failed
+    #[error("Search failed")]
+    Search(#[source] TantivyError),
+}
+
+impl DictionaryError {
+    pub(crate) fn io(context: impl Into<String>, source: std::io::Error) -> Self {
+        DictionaryError::Io {
+            context: context.into(),
+            source,
+        }
+    }
+
+    pub(crate) fn index(context: impl Into<String>, source: impl Into<TantivyError>) -> Self {
+        DictionaryError::Index {
+            context: context.into(),
+            source: source.into(),
+        }
+    }
+}
+
+pub type Result<T, E = DictionaryError> = std::result::Result<T, E>;
@@ -0,0 +1,94 @@
+use crate::Result;
+use log::warn;
+use reqwest::IntoUrl;
+use std::borrow::Cow;
+use std::time::Duration;
+
+/// How dictionary HTML is downloaded.
+#[derive(Debug, Clone)]
+pub struct FetchOptions {
+    /// The time allowed for each request, from connecting to reading the whole body.
+    pub timeout: Duration,
+    /// How many times a request that failed to connect, timed out or got a 5xx response is
+    /// retried.
+    pub retries: u32,
+    /// The wait before the first retry, doubling for each retry after.
+    pub backoff: Duration,
+}
+
+impl Default for FetchOptions {
+    fn default() -> Self {
+        FetchOptions {
+            timeout: Duration::from_secs(30),
+            retries: 2,
+            backoff: Duration::from_millis(500),
+        }
+    }
+}
+
+#[cfg(feature = "fetch")]
+pub(crate) async fn fetch<T: IntoUrl>(url: T, opts: &FetchOptions) -> Result<String> {
+    let url = url.into_url()?;
+    let client = reqwest::Client::builder().timeout(opts.timeout).build()?;
+    let mut backoff = opts.backoff;
+    let mut attempt = 0;
+    loop {
+        let result = match client.get(url.clone()).send().await {
+            Ok(response) => match response.error_for_status() {
+                Ok(response) => response.bytes().await,
+                Err(e) => Err(e),
+            },
+            Err(e) => Err(e),
+        };
+        match result {
+            Err(e) if attempt < opts.retries && is_transient(&e) => {
+                attempt += 1;
+                warn!(
+                    "Fetching {} failed ({}), retry {} in {:?}",
+                    url, e, attempt, backoff
+                );
+                tokio::time::sleep(backoff).await;
+                backoff *= 2;
+            }
+            result => {
+                return crate::decode_html(&result?[..], false, url.as_str()).map(Cow::into_owned)
+            }
+        }
+    }
+}
+
+/// Like `fetch`, blocking the current thread rather than needing an async runtime.
+#[cfg(feature = "blocking")]
+pub(crate) fn fetch_blocking<T: IntoUrl>(url: T, opts: &FetchOptions) -> Result<String> {
+    let url = url.into_url()?;
+    let client = reqwest::blocking::Client::builder()
+        .timeout(opts.timeout)
+        .build()?;
+    let mut backoff = opts.backoff;
+    let mut attempt = 0;
+    loop {
+        let result = client
+            .get(url.clone())
+            .send()
+            .and_then(|response| response.error_for_status())
+            .and_then(|response| response.bytes());
+        match result {
+            Err(e) if attempt < opts.retries && is_transient(&e) => {
+                attempt += 1;
+                warn!(
+                    "Fetching {} failed ({}), retry {} in {:?}",
+                    url, e, attempt, backoff
+                );
+                std::thread::sleep(backoff);
+                backoff *= 2;
+            }
+            result => {
+                return crate::decode_html(&result?[..], false, url.as_str()).map(Cow::into_owned)
+            }
+        }
+    }
+}
+
+fn is_transient(e: &reqwest::Error) -> bool {
+    e.is_connect() || e.is_timeout() || e.status().is_some_and(|s| s.is_server_error())
+}
@@ -0,0 +1,141 @@
+/// What an entry's leading grammatical abbreviation says about the word, e.g. "sm." for a
+/// strong masculine noun or "wv." for a weak verb.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Grammar {
+    pub class: WordClass,
+    /// The gender of a noun.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub gender: Option<Gender>,
+    /// How the word inflects: a noun's declension or a verb's conjugation.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub inflection: Option<Inflection>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum WordClass {
+    Noun,
+    Adjective,
+    Adverb,
+    Verb,
+    Preposition,
+    Conjunction,
+    Pronoun,
+    Interjection,
+    Numeral,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Gender {
+    Masculine,
+    Feminine,
+    Neuter,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Inflection {
+    Strong,
+    Weak,
+    /// An irregular verb such as "beon" or "willan".
+    Anomalous,
+}
+
+impl Grammar {
+    /// Read a part of speech abbreviation as the dictionaries write it. Abbreviations this
+    /// doesn't know, and those like "pl." that don't settle the word class, give `None`.
+    pub fn from_abbreviation(abbreviation: &str) -> Option<Self> {
+        use Gender::*;
+        use Inflection::*;
+        use WordClass::*;
+
+        let noun = |gender, inflection| Grammar {
+            class: Noun,
+            gender: Some(gender),
+            inflection,
+        };
+        let verb = |inflection| Grammar {
+            class: Verb,
+            gender: None,
+            inflection,
+        };
+        let other = |class| Grammar {
+            class,
+            gender: None,
+            inflection: None,
+        };
+        Some(match abbreviation {
+            "m." => noun(Masculine, None),
+            "f." => noun(Feminine, None),
+            "n." => noun(Neuter, None),
+            "sm." => noun(Masculine, Some(Strong)),
+            "sf." => noun(Feminine, Some(Strong)),
+            "sn." => noun(Neuter, Some(Strong)),
+            "wm." => noun(Masculine, Some(Weak)),
+            "wf." => noun(Feminine, Some(Weak)),
+            "wn." => noun(Neuter, Some(Weak)),
+            "v." => verb(None),
+            "sv." => verb(Some(Strong)),
+            "wv." => verb(Some(Weak)),
+            "anv." => verb(Some(Anomalous)),
+            "adj." => other(Adjective),
+            "adv." => other(Adverb),
+            "prep." => other(Preposition),
+            "conj." => other(Conjunction),
+            "pron." => other(Pronoun),
+            "interj." => other(Interjection),
+            "num." => other(Numeral),
+            _ => return None,
+        })
+    }
+}
+
+/// The lowercase names these are indexed under, so that queries can filter on them, e.g.
+/// `gender:feminine`.
+macro_rules! names {
+    ($type:ident { $($variant:ident => $name:literal),* $(,)? }) => {
+        impl $type {
+            pub fn as_str(self) -> &'static str {
+                match self {
+                    $($type::$variant => $name,)*
+                }
+            }
+
+            pub(crate) fn from_name(name: &str) -> Option<Self> {
+                match name {
+                    $($name => Some($type::$variant),)*
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+names!(WordClass {
+    Noun => "noun",
+    Adjective => "adjective",
+    Adverb => "adverb",
+    Verb => "verb",
+    Preposition => "preposition",
+    Conjunction => "conjunction",
+    Pronoun => "pronoun",
+    Interjection => "interjection",
+    Numeral => "numeral",
+});
+
+names!(Gender {
+    Masculine => "masculine",
+    Feminine => "feminine",
+    Neuter => "neuter",
+});
+
+names!(Inflection {
+    Strong => "strong",
+    Weak => "weak",
+    Anomalous => "anomalous",
+});
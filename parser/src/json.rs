@@ -0,0 +1,125 @@
+//! Reading and writing entries as JSON, led by a header naming the format version so that
+//! readers can tell whether they understand the entries that follow.
+
+use crate::{DictionaryError, Entry, Result};
+use log::warn;
+use std::io::{BufRead, Read, Write};
+
+/// The version of the JSON export format. It changes whenever an `Entry` field is added,
+/// removed or changes meaning.
+pub const EXPORT_FORMAT_VERSION: u32 = 1;
+
+/// The `Entry` fields an export of `EXPORT_FORMAT_VERSION` may contain.
+const FIELDS: &[&str] = &[
+    "word",
+    "definition",
+    "part_of_speech",
+    "grammar",
+    "id",
+    "see_also",
+    "original_word",
+    "ordinal",
+    "senses",
+    "source",
+];
+
+/// The metadata leading an export: the first line of JSON Lines, or the fields alongside
+/// `entries` in a JSON document.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ExportHeader {
+    pub format_version: u32,
+    /// The names of the fields entries may have.
+    pub fields: Vec<String>,
+}
+
+impl ExportHeader {
+    /// The header for exports written by this version of the crate.
+    pub fn current() -> Self {
+        ExportHeader {
+            format_version: EXPORT_FORMAT_VERSION,
+            fields: FIELDS.iter().map(|&field| field.to_owned()).collect(),
+        }
+    }
+
+    fn check(&self) -> Result<()> {
+        if self.format_version != EXPORT_FORMAT_VERSION {
+            return Err(DictionaryError::IncompatibleExport(self.format_version));
+        }
+        Ok(())
+    }
+}
+
+/// A JSON export of a whole dictionary, as written by `write_json`.
+#[derive(serde::Serialize)]
+struct Export<'a> {
+    #[serde(flatten)]
+    header: ExportHeader,
+    entries: &'a [Entry],
+}
+
+fn write_error(e: serde_json::Error) -> DictionaryError {
+    DictionaryError::io("Couldn't write JSON", e.into())
+}
+
+/// Write the header line, then one line per entry.
+pub(crate) fn write_json_lines<W: Write>(mut writer: W, entries: &[Entry]) -> Result<()> {
+    serde_json::to_writer(&mut writer, &ExportHeader::current()).map_err(write_error)?;
+    for entry in entries {
+        writeln!(writer).map_err(|e| DictionaryError::io("Couldn't write JSON", e))?;
+        serde_json::to_writer(&mut writer, entry).map_err(write_error)?;
+    }
+    writeln!(writer).map_err(|e| DictionaryError::io("Couldn't write JSON", e))
+}
+
+/// Write the header's fields and the entries as one JSON object.
+pub(crate) fn write_json<W: Write>(writer: W, entries: &[Entry]) -> Result<()> {
+    let export = Export {
+        header: ExportHeader::current(),
+        entries,
+    };
+    serde_json::to_writer(writer, &export).map_err(write_error)
+}
+
+/// Read a JSON object written by `write_json`, refusing one of another format version.
+pub(crate) fn read_json<R: Read>(reader: R) -> Result<Vec<Entry>> {
+    let invalid = |source: serde_json::Error| DictionaryError::Json {
+        line: source.line(),
+        source,
+    };
+    let mut export: serde_json::Value = serde_json::from_reader(reader).map_err(invalid)?;
+    // Check the version before the entries, whose shape may be what changed
+    let header: ExportHeader = serde_json::from_value(export.clone()).map_err(invalid)?;
+    header.check()?;
+    serde_json::from_value(export["entries"].take()).map_err(invalid)
+}
+
+/// Read JSON Lines of entries. A leading header line is checked against
+/// `EXPORT_FORMAT_VERSION`; input without one, e.g. written by hand, is read as the current
+/// format.
+pub(crate) fn read_json_lines<R: Read>(reader: R, skip_invalid: bool) -> Result<Vec<Entry>> {
+    let mut entries = Vec::new();
+    let mut first = true;
+    for (i, line) in std::io::BufReader::new(reader).lines().enumerate() {
+        let line = line.map_err(|e| DictionaryError::io("Couldn't read JSON Lines", e))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if std::mem::take(&mut first) {
+            if let Ok(header) = serde_json::from_str::<ExportHeader>(&line) {
+                header.check()?;
+                continue;
+            }
+        }
+        match serde_json::from_str(&line) {
+            Ok(entry) => entries.push(entry),
+            Err(e) if skip_invalid => warn!("Skipping line {}: {}", i + 1, e),
+            Err(source) => {
+                return Err(DictionaryError::Json {
+                    line: i + 1,
+                    source,
+                })
+            }
+        }
+    }
+    Ok(entries)
+}
@@ -1,26 +1,393 @@
-use anyhow::Context;
-use log::debug;
+use ego_tree::NodeId;
+use log::{debug, warn};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+#[cfg(any(feature = "fetch", feature = "blocking"))]
 use reqwest::IntoUrl;
 use scraper::{ElementRef, Html, Node, Selector};
-use std::path::Path;
-use tantivy::collector::TopDocs;
+use std::borrow::Cow;
+use std::collections::{BTreeSet, HashSet};
+use std::ops::{Bound, Range};
+#[cfg(feature = "fs")]
+use std::path::{Path, PathBuf};
+use tantivy::collector::{Count, TopDocs};
+#[cfg(feature = "fs")]
+use tantivy::directory::MmapDirectory;
 use tantivy::doc;
-use tantivy::query::QueryParser;
+use tantivy::query::{
+    BooleanQuery, FuzzyTermQuery, Occur, Query, QueryParser, QueryParserError, RangeQuery,
+    RegexQuery, TermQuery,
+};
 use tantivy::schema::*;
+use tantivy::DocAddress;
 use tantivy::Index;
 use tantivy::ReloadPolicy;
 use tantivy::Searcher;
+use tantivy::SnippetGenerator;
+use tantivy::{IndexReader, IndexWriter};
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
 
-#[derive(Debug, Clone, PartialEq)]
+#[cfg(feature = "fs")]
+mod cache;
+#[cfg(feature = "fs")]
+mod charset;
+mod collation;
+mod diagnostic;
+mod error;
+#[cfg(any(feature = "fetch", feature = "blocking"))]
+mod fetch;
+mod grammar;
+#[cfg(feature = "serde")]
+mod json;
+mod prefix_query;
+mod query_error;
+mod runic;
+#[cfg(feature = "server")]
+mod server;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+#[cfg(feature = "fs")]
+mod stardict;
+mod tokenizer;
+#[cfg(feature = "wasm")]
+mod wasm;
+
+#[cfg(feature = "fs")]
+pub use cache::{default_cache_dir, UrlCache};
+pub use collation::{compare_old_english, SortOrder};
+pub use diagnostic::{ParagraphDiagnostic, ParagraphOutcome, WordDiagnostic};
+pub use error::{DictionaryError, Result};
+#[cfg(any(feature = "fetch", feature = "blocking"))]
+pub use fetch::FetchOptions;
+pub use grammar::{Gender, Grammar, Inflection, WordClass};
+#[cfg(feature = "serde")]
+pub use json::{ExportHeader, EXPORT_FORMAT_VERSION};
+pub use runic::{is_runic, transliterate_futhorc};
+#[cfg(feature = "server")]
+pub use server::serve;
+#[cfg(feature = "wasm")]
+pub use wasm::WasmDictionary;
+
+/// Grammatical abbreviations that may lead a definition, e.g. "m." for a masculine noun.
+const PARTS_OF_SPEECH: &[&str] = &[
+    "m.", "f.", "n.", "sm.", "sf.", "sn.", "wm.", "wf.", "wn.", "adj.", "adv.", "v.", "sv.", "wv.",
+    "anv.", "prep.", "conj.", "pron.", "interj.", "num.", "pl.",
+];
+
+/// The shortest substring `Dictionary::words_containing` searches for without a warning.
+const MIN_SUBSTRING_LEN: usize = 2;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct Entry {
     pub word: String,
     pub definition: String,
+    /// The raw grammatical abbreviation leading the definition, e.g. "m." or "adj."
+    pub part_of_speech: Option<String>,
+    /// What `part_of_speech` says about the word, when it's an abbreviation `Grammar` knows.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub grammar: Option<Grammar>,
+    /// The `word_` anchor id of the source paragraph
+    pub id: Option<String>,
+    /// Headwords the definition refers to with "See ..." or "cf. ...".
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+    pub see_also: Vec<String>,
+    /// The headword as written in the source, when normalization changed it.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub original_word: Option<String>,
+    /// The entry's position among those parsed from its source document, counting from 0.
+    /// Parsing the same file always numbers its entries the same way; entries made by hand
+    /// have none.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub ordinal: Option<u64>,
+    /// The definition split into its senses, when parsed with `ParseOptions::split_senses`.
+    /// `definition` still holds the whole text.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+    pub senses: Vec<String>,
+    /// A label for the dictionary or edition the entry came from, e.g. "bosworth-toller", set
+    /// with `ParseOptions::source`, so entries merged from several remain distinguishable.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub source: Option<String>,
 }
 
-impl TryFrom<ElementRef<'_>> for Entry {
-    type Error = anyhow::Error;
+/// Classes of the page markers and footnote references scattered through the source HTML.
+const ARTIFACT_CLASSES: [&str; 4] = ["pagenum", "pageno", "fnanchor", "footnote"];
+
+/// Replace wynn with the w modern transcriptions use.
+fn normalize_wynn(text: &str) -> String {
+    text.replace('ƿ', "w").replace('Ƿ', "W")
+}
+
+/// Split a definition into senses. Numbered senses win where there are at least two of them,
+/// numbered from 1 in order; any text before the first is kept as a sense of its own. Without
+/// them, senses are separated by semicolons that aren't within brackets. A definition with
+/// neither is a single sense.
+fn split_senses(definition: &str) -> Vec<String> {
+    let senses = numbered_senses(definition);
+    // The text before "1." and at least two numbered senses
+    let senses = if senses.len() > 2 {
+        senses
+    } else {
+        semicolon_senses(definition)
+    };
+    senses
+        .into_iter()
+        .map(|sense| sense.trim_matches(|c: char| c.is_whitespace() || c == ';' || c == ','))
+        .filter(|sense| !sense.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
+
+/// The text before the marker "1.", then the text following it and each of "2.", "3.", ... in
+/// turn. A marker must start a clause, so that a number ending a sentence, like the year in
+/// "died in 871. He", isn't taken for one. Empty unless a "1." is found.
+fn numbered_senses(definition: &str) -> Vec<&str> {
+    let mut markers: Vec<(usize, usize)> = Vec::new();
+    let mut from = 0;
+    loop {
+        let marker = format!("{}.", markers.len() + 1);
+        let found = definition[from..]
+            .match_indices(&marker)
+            .map(|(i, _)| from + i)
+            .find(|&i| {
+                starts_clause(definition, i)
+                    && definition[i + marker.len()..].starts_with(char::is_whitespace)
+            });
+        match found {
+            Some(start) => {
+                markers.push((start, start + marker.len()));
+                from = start + marker.len();
+            }
+            None => break,
+        }
+    }
+    let Some(&(first, _)) = markers.first() else {
+        return Vec::new();
+    };
+    let mut senses = vec![&definition[..first]];
+    for (i, &(_, text_start)) in markers.iter().enumerate() {
+        let end = markers
+            .get(i + 1)
+            .map_or(definition.len(), |&(start, _)| start);
+        senses.push(&definition[text_start..end]);
+    }
+    senses
+}
+
+/// Whether the text at byte `i` begins the definition or follows whitespace after a full stop,
+/// semicolon or colon.
+fn starts_clause(text: &str, i: usize) -> bool {
+    let before = &text[..i];
+    let trimmed = before.trim_end();
+    trimmed.is_empty() || (trimmed.len() < before.len() && trimmed.ends_with(['.', ';', ':']))
+}
+
+/// The parts of a definition between semicolons, ignoring those within brackets, as in "light
+/// (of day; of a lamp); the world".
+fn semicolon_senses(definition: &str) -> Vec<&str> {
+    let mut senses = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in definition.char_indices() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth = depth.saturating_sub(1),
+            ';' if depth == 0 => {
+                senses.push(&definition[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    senses.push(&definition[start..]);
+    senses
+}
+
+/// Markers introducing a cross-reference to other headwords.
+const CROSS_REFERENCE_MARKERS: [&str; 3] = ["See ", "cf. ", "Cf. "];
+
+/// The headwords a definition refers to, as in "See cyning." or "cf. cyne-dom, cynn". A
+/// reference runs to the next full stop, semicolon or bracket, and is a comma or "and"
+/// separated list of single words; anything longer is taken to be prose.
+fn cross_references(definition: &str) -> Vec<String> {
+    let mut references = Vec::new();
+    for marker in CROSS_REFERENCE_MARKERS {
+        for (start, _) in definition.match_indices(marker) {
+            let rest = &definition[start + marker.len()..];
+            let end = rest.find(['.', ';', '(', ')']).unwrap_or(rest.len());
+            let words: Vec<&str> = rest[..end]
+                .split(',')
+                .flat_map(|part| part.split(" and "))
+                .map(str::trim)
+                .filter(|word| !word.is_empty())
+                .collect();
+            if words.iter().all(|word| !word.contains(char::is_whitespace)) {
+                for word in words {
+                    if !references.iter().any(|r| r == word) {
+                        references.push(word.to_owned());
+                    }
+                }
+            }
+        }
+    }
+    references
+}
+
+/// Whether an element is a page number or footnote marker rather than part of an entry.
+fn is_artifact(el: &scraper::node::Element) -> bool {
+    el.name() == "sup" || el.classes().any(|class| ARTIFACT_CLASSES.contains(&class))
+}
+
+/// The text within `el`, leaving out page markers and footnote references.
+fn text_without_artifacts(el: ElementRef) -> String {
+    el.descendants()
+        .filter(|node| {
+            !node
+                .ancestors()
+                .filter_map(|n| n.value().as_element())
+                .any(is_artifact)
+        })
+        .filter_map(|node| node.value().as_text().map(|t| &**t))
+        .collect()
+}
+
+/// `definition` without a leading repeat of one of its headwords, as some layouts print the
+/// headword again in plain text after the bold one.
+fn strip_headword<'a>(definition: &'a str, words: &[String]) -> &'a str {
+    for word in words {
+        if let Some(rest) = definition.strip_prefix(word.as_str()) {
+            if rest.is_empty() || rest.starts_with(|c: char| c.is_whitespace() || c == ',') {
+                return rest.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+            }
+        }
+    }
+    definition
+}
+
+/// Whether `paragraph` starts a dictionary entry: its first child has a `word_` id.
+fn is_entry_paragraph(paragraph: &ElementRef) -> bool {
+    paragraph
+        .first_child()
+        .and_then(|child| child.value().as_element())
+        .and_then(|el| el.attr("id"))
+        .is_some_and(|id| id.starts_with("word_"))
+}
+
+/// The paragraphs after an entry's paragraph that its definition spills over into: those up
+/// to the next entry paragraph, or to anything between them other than whitespace.
+fn continuations<'a>(paragraph: ElementRef<'a>) -> impl Iterator<Item = ElementRef<'a>> {
+    paragraph
+        .next_siblings()
+        .filter(|node| match node.value() {
+            Node::Text(text) => !text.trim().is_empty(),
+            Node::Comment(_) => false,
+            _ => true,
+        })
+        .map_while(ElementRef::wrap)
+        .take_while(|el| el.value().name() == "p" && !is_entry_paragraph(el))
+}
+
+/// Decode HTML entities left in text content, e.g. "&aelig;" to "æ" or "&#254;" to "þ". The
+/// HTML parser decodes entities once, but some sources escape them twice.
+fn decode_entities(text: &str) -> Cow<'_, str> {
+    html_escape::decode_html_entities(text)
+}
+
+/// Fold Old English spelling to plain letters for diacritic-insensitive matching: macrons
+/// and other diacritics are stripped, "æ" becomes "ae" and "þ"/"ð" become "th".
+pub fn fold(text: &str) -> String {
+    let mut folded = String::with_capacity(text.len());
+    for c in text.nfd().filter(|c| !is_combining_mark(*c)) {
+        match c {
+            'æ' => folded.push_str("ae"),
+            'Æ' => folded.push_str("Ae"),
+            'þ' | 'ð' => folded.push_str("th"),
+            'Þ' | 'Ð' => folded.push_str("Th"),
+            c => folded.push(c),
+        }
+    }
+    folded
+}
+
+/// Parse `query` with `parser`, describing what's wrong with it if it's invalid.
+fn parse_query(parser: &QueryParser, query: &str) -> Result<Box<dyn Query>> {
+    if query_error::nesting_depth(query) > query_error::MAX_DEPTH {
+        return Err(DictionaryError::InvalidQuery(format!(
+            "parentheses are nested more than {} deep",
+            query_error::MAX_DEPTH
+        )));
+    }
+    parser
+        .parse_query(query)
+        .map_err(|source| query_error::error(source, query))
+}
+
+/// The key `define` matches headwords on: lowercased, with "æ" written "ae".
+fn headword_key(word: &str) -> String {
+    word.to_lowercase().replace('æ', "ae")
+}
+
+/// Split a leading part-of-speech abbreviation off the front of a definition.
+fn split_part_of_speech(definition: &str) -> (Option<String>, &str) {
+    let (token, rest) = definition
+        .split_once(char::is_whitespace)
+        .unwrap_or((definition, ""));
+    if PARTS_OF_SPEECH.contains(&token) {
+        (Some(token.to_owned()), rest.trim_start())
+    } else {
+        (None, definition)
+    }
+}
+
+/// How entries are extracted from dictionary HTML.
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    /// Keep emphasis within definitions as Markdown, wrapping italic text (usually Latin
+    /// glosses) in `*...*` and bold text in `**...**`. Off, definitions are plain text.
+    pub markdown: bool,
+    /// Spell wynn (ƿ, Ƿ) as w in headwords and definitions, so that "word" finds "ƿord". The
+    /// headword as written is kept in `Entry::original_word`. On by default.
+    pub normalize_wynn: bool,
+    /// Skip paragraphs whose definition is empty or only whitespace, counting them in
+    /// `ParseReport::skipped`. Off, they're kept as entries with a blank definition. On by
+    /// default. Headwords that are themselves empty are always dropped.
+    pub skip_empty_definitions: bool,
+    /// Fill in `Entry::senses`, splitting definitions on numbered senses, as in "1. a king.
+    /// 2. God.", or failing those on semicolons outside brackets. Off by default.
+    pub split_senses: bool,
+    /// Label every entry with where it came from, in `Entry::source`. None by default.
+    pub source: Option<String>,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            markdown: false,
+            normalize_wynn: true,
+            skip_empty_definitions: true,
+            split_senses: false,
+            source: None,
+        }
+    }
+}
+
+impl Entry {
+    /// Convert a dictionary paragraph into one `Entry` per headword. Paragraphs that list
+    /// several spellings as consecutive bold words (homographs) share the same definition.
+    pub fn from_paragraph(paragraph_el: ElementRef) -> Result<Vec<Self>> {
+        Self::from_paragraph_with_options(paragraph_el, &ParseOptions::default())
+    }
 
-    fn try_from(paragraph_el: ElementRef) -> anyhow::Result<Self> {
+    /// Like `from_paragraph`, with control over how the definition is extracted.
+    ///
+    /// Paragraphs following this one without a `word_` anchor of their own continue its
+    /// definition, as long entries do in the source, so their text is included too.
+    pub fn from_paragraph_with_options(
+        paragraph_el: ElementRef,
+        options: &ParseOptions,
+    ) -> Result<Vec<Self>> {
         debug!(
             "Children {:?}",
             paragraph_el
@@ -28,220 +395,4570 @@ impl TryFrom<ElementRef<'_>> for Entry {
                 .map(|c| c.value().as_element())
                 .collect::<Vec<_>>()
         );
-        let selector = Selector::parse("b").unwrap();
-        let word_el = paragraph_el.select(&selector).take(1).next().unwrap();
-        let anchor = paragraph_el.first_child().unwrap().value();
+        let anchor = paragraph_el
+            .first_child()
+            .ok_or_else(|| DictionaryError::Parse("Paragraph has no anchor".to_owned()))?
+            .value();
 
         debug!(
             r#"Anchor {:?}
             "#,
             anchor,
         );
-        let mut id = "";
-        let mut word = "";
+        let mut id = None;
+        let mut words = Vec::new();
+        let mut in_headwords = true;
         let mut definition = String::new();
 
+        let bold_text = |el: ElementRef| {
+            let word = el.text().collect::<String>();
+            decode_entities(word.trim()).into_owned()
+        };
         for child in paragraph_el.children() {
-            debug!(
-                "first child is {:?}",
-                child.first_child().map(|c| c.value())
-            );
-            if let Some(el) = child.value().as_element() {
-                if let Some(id_v) = el.attr("id") {
-                    if id_v.starts_with("word_") {
-                        id = id_v;
-                    }
-                    if let Some(bold_el) = word_el.first_child() {
-                        if let Some(word_text) = bold_el.value().as_text() {
-                            word = word_text.trim();
+            let (text, emphasis) = match ElementRef::wrap(child) {
+                Some(el) if is_artifact(el.value()) => continue,
+                Some(el) => {
+                    // The anchor only carries the id: its text, if any, is neither headword
+                    // nor definition, except for headwords some layouts put in bold inside it
+                    if let Some(id_v) = el.value().attr("id").filter(|v| v.starts_with("word_")) {
+                        id = Some(id_v);
+                        if in_headwords {
+                            let bold = Selector::parse("b").expect("Valid selector");
+                            words.extend(el.select(&bold).map(bold_text));
                         }
+                        continue;
                     }
+                    // Headwords are the run of bold elements leading the paragraph
+                    if in_headwords && el.value().name() == "b" {
+                        words.push(bold_text(el));
+                        continue;
+                    }
+                    let emphasis = match el.value().name() {
+                        "i" | "em" if options.markdown => "*",
+                        "b" | "strong" if options.markdown => "**",
+                        _ => "",
+                    };
+                    (text_without_artifacts(el), emphasis)
                 }
-            }
-            if let Some(txt) = child.first_child().map(|c| c.value()) {
-                if let Some(txt_str) = txt.as_text() {
-                    definition.push_str(&format!("{} ", txt_str.replace("\n", " ")));
+                None => match child.value() {
+                    Node::Text(txt) => (txt.to_string(), ""),
+                    _ => continue,
+                },
+            };
+            // The definition starts at the first text after the headwords that isn't just
+            // the commas and spaces separating them, without those leading it
+            let text = if in_headwords {
+                let rest = text.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+                if rest.trim_end().is_empty() {
+                    continue;
                 }
+                in_headwords = false;
+                rest.to_owned()
+            } else {
+                text
+            };
+            let txt = text.replace('\n', " ");
+            let txt = decode_entities(&txt);
+            if emphasis.is_empty() || txt.trim().is_empty() {
+                definition.push_str(&format!("{} ", txt));
+            } else {
+                definition.push_str(&format!("{0}{1}{0} ", emphasis, txt.trim()));
             }
         }
 
-        debug!("ID: {}", id);
-        debug!("Word: {}", word);
+        for continuation in continuations(paragraph_el) {
+            definition.push(' ');
+            definition.push_str(&decode_entities(&text_without_artifacts(continuation)));
+        }
+
+        debug!("ID: {:?}", id);
+        debug!("Words: {:?}", words);
         debug!("Definition: {}", definition);
-        Ok(Entry {
-            word: word.to_string(),
-            definition: definition.trim().to_owned(),
+        words.retain(|word| !word.is_empty());
+        if words.is_empty() {
+            return Err(DictionaryError::Parse(
+                "Paragraph has no bold headword".to_owned(),
+            ));
+        }
+        // Each text node was pushed with a trailing space, so collapse the runs that leaves
+        let definition = definition.split_whitespace().collect::<Vec<_>>().join(" ");
+        let definition = strip_headword(&definition, &words);
+        let (part_of_speech, definition) = split_part_of_speech(definition);
+        debug!("Part of speech: {:?}", part_of_speech);
+        let see_also = cross_references(definition);
+        let entries = words
+            .into_iter()
+            .map(|word| Entry {
+                word,
+                definition: definition.to_owned(),
+                grammar: part_of_speech
+                    .as_deref()
+                    .and_then(Grammar::from_abbreviation),
+                part_of_speech: part_of_speech.clone(),
+                id: id.map(str::to_owned),
+                see_also: see_also.clone(),
+                original_word: None,
+                ordinal: None,
+                senses: Vec::new(),
+                source: None,
+            })
+            .map(|entry| entry.normalize(options))
+            .collect();
+        require_definition(entries, options)
+    }
+
+    /// Apply the spelling normalizations chosen in `options`, split the normalized definition
+    /// into senses if asked to and label the entry with its source.
+    fn normalize(mut self, options: &ParseOptions) -> Self {
+        if options.source.is_some() {
+            self.source = options.source.clone();
+        }
+        if options.normalize_wynn {
+            let word = normalize_wynn(&self.word);
+            if word != self.word {
+                self.original_word = Some(std::mem::replace(&mut self.word, word));
+            }
+            self.definition = normalize_wynn(&self.definition);
+            self.see_also = self.see_also.iter().map(|w| normalize_wynn(w)).collect();
+        }
+        if options.split_senses {
+            self.senses = split_senses(&self.definition);
+        }
+        self
+    }
+
+    /// Convert an element chosen by a custom entry selector into one `Entry` per headword: the
+    /// element itself or those within it matching `word_selector`. The definition is the rest
+    /// of the element's text, or, for a `<dt>` with nothing but headwords in it, the text of
+    /// the `<dd>` following it.
+    pub fn from_element(entry_el: ElementRef, word_selector: &Selector) -> Result<Vec<Self>> {
+        // The entry element may itself be the headword, as with a `<dt>`
+        let word_els: Vec<ElementRef> = Some(entry_el)
+            .filter(|el| word_selector.matches(el))
+            .into_iter()
+            .chain(entry_el.select(word_selector))
+            .collect();
+        let words: Vec<String> = word_els
+            .iter()
+            .map(|el| decode_entities(el.text().collect::<String>().trim()).into_owned())
+            .filter(|word| !word.is_empty())
+            .collect();
+        if words.is_empty() {
+            return Err(DictionaryError::Parse(
+                "Entry has no element matching the word selector".to_owned(),
+            ));
+        }
+
+        // Text belongs to the definition unless it sits within a headword element
+        let word_ids: HashSet<NodeId> = word_els.iter().map(|el| el.id()).collect();
+        let in_word = |node: ego_tree::NodeRef<Node>| {
+            std::iter::once(node)
+                .chain(node.ancestors())
+                .take_while(|n| n.id() != entry_el.id())
+                .chain(std::iter::once(*entry_el))
+                .any(|n| word_ids.contains(&n.id()))
+        };
+        let in_artifact = |node: ego_tree::NodeRef<Node>| {
+            node.ancestors()
+                .filter_map(|n| n.value().as_element())
+                .any(is_artifact)
+        };
+        let mut definition: String = entry_el
+            .descendants()
+            .filter(|n| !in_word(*n) && !in_artifact(*n))
+            .filter_map(|n| n.value().as_text().map(|t| &**t))
+            .collect();
+        if definition.trim().is_empty() && entry_el.value().name() == "dt" {
+            let dd = entry_el
+                .next_siblings()
+                .find_map(ElementRef::wrap)
+                .filter(|el| el.value().name() == "dd");
+            if let Some(dd) = dd {
+                definition = dd.text().collect();
+            }
+        }
+        let definition = decode_entities(&definition)
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        // Drop the separators between the headwords and the definition, as in `from_paragraph`
+        let definition = definition.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+        let (part_of_speech, definition) = split_part_of_speech(definition);
+        let see_also = cross_references(definition);
+        let id = entry_el.value().attr("id");
+        Ok(words
+            .into_iter()
+            .map(|word| Entry {
+                word,
+                definition: definition.to_owned(),
+                grammar: part_of_speech
+                    .as_deref()
+                    .and_then(Grammar::from_abbreviation),
+                part_of_speech: part_of_speech.clone(),
+                id: id.map(str::to_owned),
+                see_also: see_also.clone(),
+                original_word: None,
+                ordinal: None,
+                senses: Vec::new(),
+                source: None,
+            })
+            .collect())
+    }
+}
+
+/// Reject a paragraph's entries, which share one definition, if it's blank and `options` says
+/// to skip such paragraphs.
+fn require_definition(entries: Vec<Entry>, options: &ParseOptions) -> Result<Vec<Entry>> {
+    match entries.first() {
+        Some(entry) if options.skip_empty_definitions && entry.definition.trim().is_empty() => {
+            Err(DictionaryError::Parse(format!(
+                "Paragraph for {} has an empty definition",
+                entry.word
+            )))
+        }
+        _ => Ok(entries),
+    }
+}
+
+/// Formats as `word — definition`, or `word (m.) — definition` with a part of speech.
+impl std::fmt::Display for Entry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.part_of_speech {
+            Some(part_of_speech) => write!(
+                f,
+                "{} ({}) — {}",
+                self.word, part_of_speech, self.definition
+            ),
+            None => write!(f, "{} — {}", self.word, self.definition),
+        }
+    }
+}
+
+impl TryFrom<ElementRef<'_>> for Entry {
+    type Error = DictionaryError;
+
+    /// Convert a dictionary paragraph into the `Entry` for its first headword.
+    fn try_from(paragraph_el: ElementRef) -> Result<Self> {
+        Entry::from_paragraph(paragraph_el)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| DictionaryError::Parse("Paragraph has no bold headword".to_owned()))
+    }
+}
+
+/// A window into a ranked list of results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Page {
+    /// The number of results to skip
+    pub offset: usize,
+    /// The maximum number of results to return
+    pub limit: usize,
+}
+
+impl Default for Page {
+    fn default() -> Self {
+        Page {
+            offset: 0,
+            limit: 10,
+        }
+    }
+}
+
+/// A page of matching entries and the total number of matches across all pages.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Hits {
+    pub total: usize,
+    pub entries: Vec<Entry>,
+}
+
+/// A short fragment of a definition around the terms a query matched.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Snippet {
+    pub fragment: String,
+    /// The byte ranges of `fragment` that matched the query.
+    pub highlighted: Vec<Range<usize>>,
+}
+
+/// How an entry `Dictionary::lookup` returns matched the term.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum MatchKind {
+    /// The headword is the term, as `define` matches it.
+    Exact,
+    /// The headword is within a few edits of the term, as `search_fuzzy` matches it.
+    Fuzzy,
+}
+
+/// The smallest `DictionaryOptions::writer_heap` tantivy accepts.
+pub const MIN_WRITER_HEAP: usize = 15_000_000;
+/// The largest `DictionaryOptions::writer_heap` tantivy accepts.
+pub const MAX_WRITER_HEAP: usize = u32::MAX as usize - 1_000_001;
+const DEFAULT_WRITER_HEAP: usize = 50_000_000;
+const DEFAULT_WORD_BOOST: f32 = 3.0;
+
+/// How a `Dictionary` indexes its entries.
+///
+/// Searches are ranked with BM25, using tantivy's parameters: k1 = 1.2, so repeating a term
+/// in a definition soon stops counting for more, and b = 0.75, so a match in a short
+/// definition outranks one in a long definition. tantivy 0.21 has no setting for either, so
+/// k1 can't be tuned, and b can only be 0.75 or 0, as chosen by `length_normalization`.
+#[derive(Debug, Clone)]
+pub struct DictionaryOptions {
+    /// Lowercase words and definitions and strip common Old English inflections (-e, -as, -um,
+    /// -an), so that "stanas" finds "stan". Off, words are tokenized as plain text.
+    pub stemming: bool,
+    /// Also fold þ, ð and æ to "th" and "ae" while stemming, as `fold` does.
+    pub fold: bool,
+    /// Index only the first of any entries that share both `id` and word, or that have no `id`
+    /// and share both word and definition, so a paragraph parsed twice isn't counted twice.
+    /// Off by default.
+    pub dedup: bool,
+    /// The memory, in bytes, the index writer may use before flushing a segment to its
+    /// directory. Less is slower to index but fits smaller machines. Must be between
+    /// [`MIN_WRITER_HEAP`] and [`MAX_WRITER_HEAP`]; defaults to 50 MB.
+    pub writer_heap: usize,
+    /// How much more a match in a headword counts than one in a definition when searching both,
+    /// so that searching "light" ranks the entry for "light" above entries mentioning it.
+    /// Defaults to 3.
+    pub word_boost: f32,
+    /// Whether BM25's b is tantivy's 0.75 or 0, not a value in between. On, b = 0.75 ranks
+    /// matches in long headwords and definitions below those in short ones. Off, the index
+    /// doesn't record field lengths, which is the same as b = 0: only how often and where a
+    /// term matches counts. On by default.
+    pub length_normalization: bool,
+}
+
+impl Default for DictionaryOptions {
+    fn default() -> Self {
+        DictionaryOptions {
+            stemming: true,
+            fold: false,
+            dedup: false,
+            writer_heap: DEFAULT_WRITER_HEAP,
+            word_boost: DEFAULT_WORD_BOOST,
+            length_normalization: true,
+        }
+    }
+}
+
+impl DictionaryOptions {
+    fn validate(&self) -> Result<()> {
+        if (MIN_WRITER_HEAP..=MAX_WRITER_HEAP).contains(&self.writer_heap) {
+            Ok(())
+        } else {
+            Err(DictionaryError::InvalidWriterHeap(self.writer_heap))
+        }
+    }
+
+    /// Every combination of options that gives a distinct schema.
+    #[cfg(feature = "fs")]
+    fn variants() -> impl Iterator<Item = DictionaryOptions> {
+        [(false, false), (true, false), (true, true)]
+            .into_iter()
+            .flat_map(|(stemming, fold)| {
+                [true, false]
+                    .into_iter()
+                    .map(move |length_normalization| DictionaryOptions {
+                        stemming,
+                        fold,
+                        length_normalization,
+                        ..DictionaryOptions::default()
+                    })
+            })
+    }
+}
+
+/// How far parsing or indexing has got, as passed to a progress callback.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Progress {
+    /// `done` of the document's `total` entry paragraphs have been converted.
+    Parsed { done: usize, total: usize },
+    /// `done` of `total` entries have been added to the index.
+    Indexed { done: usize, total: usize },
+}
+
+/// Configures and builds a `Dictionary`, as an alternative to the `*_with_options`
+/// constructors. Start from `Dictionary::builder`.
+#[derive(Debug, Clone, Default)]
+pub struct DictionaryBuilder {
+    options: DictionaryOptions,
+    #[cfg(feature = "fs")]
+    dir: Option<PathBuf>,
+}
+
+impl DictionaryBuilder {
+    /// See `DictionaryOptions::stemming`.
+    pub fn stemming(mut self, stemming: bool) -> Self {
+        self.options.stemming = stemming;
+        self
+    }
+
+    /// See `DictionaryOptions::fold`.
+    pub fn folding(mut self, fold: bool) -> Self {
+        self.options.fold = fold;
+        self
+    }
+
+    /// See `DictionaryOptions::dedup`.
+    pub fn dedup(mut self, dedup: bool) -> Self {
+        self.options.dedup = dedup;
+        self
+    }
+
+    /// See `DictionaryOptions::writer_heap`.
+    pub fn writer_heap(mut self, bytes: usize) -> Self {
+        self.options.writer_heap = bytes;
+        self
+    }
+
+    /// See `DictionaryOptions::word_boost`.
+    pub fn word_boost(mut self, boost: f32) -> Self {
+        self.options.word_boost = boost;
+        self
+    }
+
+    /// See `DictionaryOptions::length_normalization`: b = 0.75 if true, 0 if false.
+    pub fn length_normalization(mut self, normalize: bool) -> Self {
+        self.options.length_normalization = normalize;
+        self
+    }
+
+    /// Write the index to `path`, as `Dictionary::create_in_dir` does, rather than keeping it
+    /// in memory.
+    #[cfg(feature = "fs")]
+    pub fn in_dir<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.dir = Some(path.into());
+        self
+    }
+
+    /// Index `entries` with the configured options.
+    pub fn build(self, entries: Vec<Entry>) -> Result<Dictionary> {
+        self.build_with_progress(entries, |_| {})
+    }
+
+    /// Like `build`, calling `on_progress` after each entry is indexed.
+    pub fn build_with_progress(
+        self,
+        entries: Vec<Entry>,
+        mut on_progress: impl FnMut(Progress),
+    ) -> Result<Dictionary> {
+        #[cfg(feature = "fs")]
+        if let Some(dir) = self.dir {
+            return Dictionary::build_in_dir(&dir, entries, &self.options, &mut on_progress);
+        }
+        Dictionary::build_in_ram(entries, &self.options, &mut on_progress)
+    }
+}
+
+/// Handles for the schema's fields, looked up once when a `Dictionary` is opened.
+#[derive(Debug, Clone, Copy)]
+struct Fields {
+    word: Field,
+    definition: Field,
+    part_of_speech: Field,
+    word_class: Field,
+    gender: Field,
+    inflection: Field,
+    id: Field,
+    see_also: Field,
+    original_word: Field,
+    word_folded: Field,
+    word_exact: Field,
+    word_key: Field,
+    ordinal: Field,
+    definition_len: Field,
+    senses: Field,
+    source: Field,
+}
+
+impl Fields {
+    fn new(schema: &Schema) -> Result<Self> {
+        let field = |name: &str| {
+            schema
+                .get_field(name)
+                .map_err(|e| DictionaryError::index(format!("Couldn't get {} field", name), e))
+        };
+        Ok(Fields {
+            word: field("word")?,
+            definition: field("definition")?,
+            part_of_speech: field("part_of_speech")?,
+            word_class: field("word_class")?,
+            gender: field("gender")?,
+            inflection: field("inflection")?,
+            id: field("id")?,
+            see_also: field("see_also")?,
+            original_word: field("original_word")?,
+            word_folded: field("word_folded")?,
+            word_exact: field("word_exact")?,
+            word_key: field("word_key")?,
+            ordinal: field("ordinal")?,
+            definition_len: field("definition_len")?,
+            senses: field("senses")?,
+            source: field("source")?,
         })
     }
 }
 
+/// Query parsers for each set of fields queries run over, built once rather than per query.
+struct QueryParsers {
+    /// Headwords and definitions, as `search` and most other methods query.
+    all: QueryParser,
+    /// Headwords only, for `define_loose`.
+    word: QueryParser,
+    /// Definitions only, for `search_definition`.
+    definition: QueryParser,
+    /// Folded headwords and definitions, for `search_folded`.
+    folded: QueryParser,
+    /// Folded headwords only, for `define_folded`.
+    word_folded: QueryParser,
+}
+
+impl QueryParsers {
+    fn new(index: &Index, fields: &Fields, word_boost: f32) -> Self {
+        let parser = |default_fields| {
+            let mut parser = QueryParser::for_index(index, default_fields);
+            parser.set_field_boost(fields.word, word_boost);
+            parser
+        };
+        QueryParsers {
+            all: parser(vec![fields.word, fields.definition]),
+            word: parser(vec![fields.word]),
+            definition: parser(vec![fields.definition]),
+            folded: parser(vec![fields.word_folded, fields.definition]),
+            word_folded: parser(vec![fields.word_folded]),
+        }
+    }
+}
+
 /// A container for indexed words and their definitions.
+///
+/// A `Dictionary` is `Send` and `Sync`, so one instance can be shared between threads, e.g.
+/// in an `Arc`, and queried from all of them at once. Every query takes its own snapshot of
+/// the index from the reader, so it sees a consistent set of segments even if a commit lands
+/// while it runs, and picks up commits made since the previous query.
 pub struct Dictionary {
     index: Index,
-    searcher: Searcher,
+    reader: IndexReader,
+    writer: Option<IndexWriter>,
+    writer_heap: usize,
+    query_parsers: QueryParsers,
+    fields: Fields,
 }
 
 impl Dictionary {
-    pub fn new(entries: Vec<Entry>) -> anyhow::Result<Self> {
+    /// The schema a dictionary index is built with for the given options.
+    fn schema(options: &DictionaryOptions) -> Schema {
+        let tokenizer = match (options.stemming, options.fold) {
+            (false, _) => "default",
+            (true, false) => tokenizer::OLD_ENGLISH,
+            (true, true) => tokenizer::OLD_ENGLISH_FOLDED,
+        };
+        let text = TextOptions::default().set_stored().set_indexing_options(
+            TextFieldIndexing::default()
+                .set_tokenizer(tokenizer)
+                .set_index_option(IndexRecordOption::WithFreqsAndPositions)
+                .set_fieldnorms(options.length_normalization),
+        );
         let mut schema_builder = Schema::builder();
-        schema_builder.add_text_field("word", TEXT | STORED);
-        schema_builder.add_text_field("definition", TEXT | STORED);
-        let schema = schema_builder.build();
-        let index = Index::create_in_ram(schema.clone());
-        let mut index_writer = index.writer(50_000_000).context("Couldn't create writer")?;
-        let word = schema.get_field("word")?;
-        let definition = schema.get_field("definition")?;
-
-        for entry in entries {
-            match index_writer.add_document(doc!(
-                word => entry.word,
-                definition => entry.definition,
-            )) {
-                Ok(_) => {}
-                Err(e) => panic!("{:?}", e),
-            }
-        }
-        index_writer.commit()?;
+        schema_builder.add_text_field("word", text.clone());
+        schema_builder.add_text_field("definition", text);
+        schema_builder.add_text_field("part_of_speech", STRING | STORED);
+        schema_builder.add_text_field("word_class", STRING | STORED);
+        schema_builder.add_text_field("gender", STRING | STORED);
+        schema_builder.add_text_field("inflection", STRING | STORED);
+        schema_builder.add_text_field("id", STRING | STORED);
+        schema_builder.add_text_field("see_also", STRING | STORED);
+        schema_builder.add_text_field("original_word", STRING | STORED);
+        schema_builder.add_text_field("word_folded", TEXT);
+        schema_builder.add_text_field("word_exact", STRING);
+        schema_builder.add_text_field("word_key", STRING);
+        schema_builder.add_u64_field("ordinal", INDEXED | STORED | FAST);
+        schema_builder.add_u64_field("definition_len", INDEXED | FAST);
+        schema_builder.add_text_field("senses", STORED);
+        schema_builder.add_text_field("source", STRING | STORED);
+        schema_builder.build()
+    }
+
+    /// Configure a dictionary step by step; see `DictionaryBuilder`.
+    pub fn builder() -> DictionaryBuilder {
+        DictionaryBuilder::default()
+    }
+
+    /// Index the given entries in memory. The whole index lives on the heap for as long as the
+    /// dictionary does; see `open` for large dictionaries.
+    pub fn new(entries: Vec<Entry>) -> Result<Self> {
+        Self::with_options(entries, &DictionaryOptions::default())
+    }
+
+    /// Like `new`, with control over how entries are indexed.
+    pub fn with_options(entries: Vec<Entry>, options: &DictionaryOptions) -> Result<Self> {
+        Self::build_in_ram(entries, options, &mut |_| {})
+    }
+
+    fn build_in_ram(
+        entries: Vec<Entry>,
+        options: &DictionaryOptions,
+        on_progress: &mut dyn FnMut(Progress),
+    ) -> Result<Self> {
+        options.validate()?;
+        let index = Index::create_in_ram(Self::schema(options));
+        Self::populate(index, entries, options, on_progress)
+    }
+
+    /// Index the given entries into a new on-disk index at `path`, creating the directory if
+    /// needed. The directory must not already contain an index.
+    #[cfg(feature = "fs")]
+    pub fn create_in_dir<P: AsRef<Path>>(path: P, entries: Vec<Entry>) -> Result<Self> {
+        Self::create_in_dir_with_options(path, entries, &DictionaryOptions::default())
+    }
+
+    /// Like `create_in_dir`, with control over how entries are indexed. The options are
+    /// recorded in the index, so `open` needs none.
+    #[cfg(feature = "fs")]
+    pub fn create_in_dir_with_options<P: AsRef<Path>>(
+        path: P,
+        entries: Vec<Entry>,
+        options: &DictionaryOptions,
+    ) -> Result<Self> {
+        Self::build_in_dir(path.as_ref(), entries, options, &mut |_| {})
+    }
+
+    #[cfg(feature = "fs")]
+    fn build_in_dir(
+        path: &Path,
+        entries: Vec<Entry>,
+        options: &DictionaryOptions,
+        on_progress: &mut dyn FnMut(Progress),
+    ) -> Result<Self> {
+        options.validate()?;
+        std::fs::create_dir_all(path).map_err(|e| {
+            DictionaryError::io(
+                format!("Couldn't create index directory {}", path.display()),
+                e,
+            )
+        })?;
+        let index = Index::create_in_dir(path, Self::schema(options)).map_err(|e| {
+            DictionaryError::index(format!("Couldn't create index in {}", path.display()), e)
+        })?;
+        Self::populate(index, entries, options, on_progress)
+    }
+
+    /// Open an index previously written by `create_in_dir`, without re-parsing any HTML. Fails
+    /// if `path` isn't a directory holding a dictionary index.
+    ///
+    /// The index files are memory-mapped rather than read in, so opening is quick and uses
+    /// little memory whatever the dictionary's size. Pages are loaded by the OS as searches
+    /// touch them, and can be evicted again under memory pressure.
+    #[cfg(feature = "fs")]
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let directory = MmapDirectory::open(path).map_err(|e| {
+            DictionaryError::index(format!("{} is not an index directory", path.display()), e)
+        })?;
+        let exists = Index::exists(&directory).map_err(|e| {
+            DictionaryError::index(format!("Couldn't read index in {}", path.display()), e)
+        })?;
+        if !exists {
+            return Err(DictionaryError::NoIndex(path.to_owned()));
+        }
+        let index = Index::open(directory).map_err(|e| {
+            DictionaryError::index(format!("Couldn't open index in {}", path.display()), e)
+        })?;
+        if !DictionaryOptions::variants().any(|options| index.schema() == Self::schema(&options)) {
+            return Err(DictionaryError::IncompatibleSchema(path.to_owned()));
+        }
+        Self::from_index(index)
+    }
+
+    fn populate(
+        index: Index,
+        entries: Vec<Entry>,
+        options: &DictionaryOptions,
+        on_progress: &mut dyn FnMut(Progress),
+    ) -> Result<Self> {
+        let entries = if options.dedup {
+            dedup(entries)
+        } else {
+            entries
+        };
+        let total = entries.len();
+        tokenizer::register(&index);
+        let fields = Fields::new(&index.schema())?;
+        #[cfg(not(target_arch = "wasm32"))]
+        let index = {
+            let mut index_writer = index
+                .writer(options.writer_heap)
+                .map_err(|e| DictionaryError::index("Couldn't create writer", e))?;
+
+            for (i, entry) in entries.into_iter().enumerate() {
+                index_writer
+                    .add_document(Self::document(&fields, entry))
+                    .map_err(|e| DictionaryError::index("Couldn't add entry to index", e))?;
+                on_progress(Progress::Indexed { done: i + 1, total });
+            }
+            index_writer
+                .commit()
+                .map_err(|e| DictionaryError::index("Couldn't commit index", e))?;
+            index
+        };
+        // Browsers can't spawn the threads an `IndexWriter` indexes on, so write a single
+        // segment on this one
+        #[cfg(target_arch = "wasm32")]
+        let index = {
+            let mut index_writer =
+                tantivy::SingleSegmentIndexWriter::new(index, options.writer_heap)
+                    .map_err(|e| DictionaryError::index("Couldn't create writer", e))?;
+            for (i, entry) in entries.into_iter().enumerate() {
+                index_writer
+                    .add_document(Self::document(&fields, entry))
+                    .map_err(|e| DictionaryError::index("Couldn't add entry to index", e))?;
+                on_progress(Progress::Indexed { done: i + 1, total });
+            }
+            index_writer
+                .finalize()
+                .map_err(|e| DictionaryError::index("Couldn't commit index", e))?
+        };
+        let mut dictionary = Self::from_index(index)?;
+        dictionary.writer_heap = options.writer_heap;
+        dictionary.set_word_boost(options.word_boost);
+        Ok(dictionary)
+    }
+
+    fn from_index(index: Index) -> Result<Self> {
+        tokenizer::register(&index);
+        // Reloading on commit watches the index from another thread, which browsers don't
+        // allow. `commit` reloads itself either way.
+        let reload_policy = if cfg!(target_arch = "wasm32") {
+            ReloadPolicy::Manual
+        } else {
+            ReloadPolicy::OnCommit
+        };
         let reader = index
             .reader_builder()
-            .reload_policy(ReloadPolicy::OnCommit)
+            .reload_policy(reload_policy)
             .try_into()
-            .context("Creating reader")?;
-        let searcher = reader.searcher();
-
-        Ok(Dictionary { index, searcher })
-    }
-
-    pub fn search(&self, query: &str, limit: Option<usize>) -> anyhow::Result<Vec<Entry>> {
-        let word = self
-            .index
-            .schema()
-            .get_field("word")
-            .context("Couldn't get word field")?;
-        let definition = self
-            .index
-            .schema()
-            .get_field("definition")
-            .context("Couldn't get definition field")?;
-        let query_parser = QueryParser::for_index(&self.index, vec![word, definition]);
-        let query = query_parser.parse_query(&query).context("Invalid query")?;
-        let top_docs = self
-            .searcher
-            .search(&query, &TopDocs::with_limit(limit.unwrap_or(10)))
-            .unwrap();
-        Ok(top_docs
-            .iter()
-            .map(|d| {
-                let entry = self.searcher.doc(d.1).expect("Failed to retrieve doc");
-                let mut word_entries = entry.get_all(word);
-                let mut def_entries = entry.get_all(definition);
-                Entry {
-                    word: word_entries.next().unwrap().as_text().unwrap().to_owned(),
-                    definition: def_entries.next().unwrap().as_text().unwrap().to_owned(),
-                }
-            })
-            .collect())
+            .map_err(|e| DictionaryError::index("Couldn't create reader", e))?;
+        let fields = Fields::new(&index.schema())?;
+        let query_parsers = QueryParsers::new(&index, &fields, DEFAULT_WORD_BOOST);
+
+        Ok(Dictionary {
+            index,
+            reader,
+            writer: None,
+            writer_heap: DEFAULT_WRITER_HEAP,
+            query_parsers,
+            fields,
+        })
     }
 
-    pub fn define(&self, query: &str) -> anyhow::Result<Vec<Entry>> {
-        let word = self
-            .index
-            .schema()
-            .get_field("word")
-            .context("Couldn't get word field")?;
-        let definition = self
-            .index
-            .schema()
-            .get_field("definition")
-            .context("Couldn't get definition field")?;
-        let query_parser = QueryParser::for_index(&self.index, vec![word]);
-        let query = query_parser.parse_query(&query).context("Invalid query")?;
-        let top_docs = self
-            .searcher
-            .search(&query, &TopDocs::with_limit(10))
-            .unwrap();
-        Ok(top_docs
-            .iter()
-            .map(|d| {
-                let entry = self.searcher.doc(d.1).expect("Failed to retrieve doc");
-                let mut word_entries = entry.get_all(word);
-                let mut def_entries = entry.get_all(definition);
-                Entry {
-                    word: word_entries.next().unwrap().as_text().unwrap().to_owned(),
-                    definition: def_entries.next().unwrap().as_text().unwrap().to_owned(),
-                }
-            })
-            .collect())
+    /// Index entries read from JSON Lines, one JSON object per line with the fields of
+    /// `Entry`, as written by `export_json_lines`. Blank lines are ignored; any other line
+    /// that isn't a valid entry fails with a [`DictionaryError::Json`] naming it. A leading
+    /// `ExportHeader` line from another format version fails with
+    /// [`DictionaryError::IncompatibleExport`].
+    #[cfg(feature = "serde")]
+    pub fn from_json_lines<R: std::io::Read>(reader: R) -> Result<Self> {
+        Self::new(json::read_json_lines(reader, false)?)
     }
-}
 
-impl TryFrom<Vec<Entry>> for Dictionary {
-    type Error = anyhow::Error;
+    /// Like `from_json_lines`, but invalid lines are logged with their line number and
+    /// skipped.
+    #[cfg(feature = "serde")]
+    pub fn from_json_lines_lossy<R: std::io::Read>(reader: R) -> Result<Self> {
+        Self::new(json::read_json_lines(reader, true)?)
+    }
 
-    fn try_from(entries: Vec<Entry>) -> Result<Self, Self::Error> {
-        Dictionary::new(entries)
+    /// Index entries read from a JSON object as written by `export_json`, failing with
+    /// [`DictionaryError::IncompatibleExport`] if it's of another format version.
+    #[cfg(feature = "serde")]
+    pub fn from_json<R: std::io::Read>(reader: R) -> Result<Self> {
+        Self::new(json::read_json(reader)?)
     }
-}
 
-pub fn parse_raw(html: String) -> anyhow::Result<Dictionary> {
-    let document = Html::parse_document(&html);
-    let paragraphs = Selector::parse("p").unwrap();
+    /// Change `DictionaryOptions::word_boost`, e.g. for an index opened with `open`.
+    pub fn set_word_boost(&mut self, boost: f32) {
+        self.query_parsers = QueryParsers::new(&self.index, &self.fields, boost);
+    }
 
-    let entries: Vec<Entry> = document
-        .select(&paragraphs)
-        .filter(|n| n.has_children())
-        .filter(|n| match n.first_child().unwrap().value() {
-            Node::Element(e) => {
-                if let Some(id) = e.attr("id") {
-                    id.starts_with("word_")
-                } else {
-                    false
-                }
-            }
-            _ => false,
-        })
-        .map(|n| n.try_into().expect("Invalid element for Entry conversion"))
-        .collect();
+    /// Add an entry to the index. It won't be found by queries until `commit` is called.
+    pub fn add_entry(&mut self, entry: Entry) -> Result<()> {
+        let document = Self::document(&self.fields, entry);
+        if self.writer.is_none() {
+            let writer = self
+                .index
+                .writer(self.writer_heap)
+                .map_err(|e| DictionaryError::index("Couldn't create writer", e))?;
+            self.writer = Some(writer);
+        }
+        if let Some(writer) = &self.writer {
+            writer
+                .add_document(document)
+                .map_err(|e| DictionaryError::index("Couldn't add entry to index", e))?;
+        }
+        Ok(())
+    }
 
-    Ok(entries.try_into()?)
-}
+    /// Add several entries to the index, as with `add_entry`.
+    pub fn extend<I: IntoIterator<Item = Entry>>(&mut self, entries: I) -> Result<()> {
+        entries
+            .into_iter()
+            .try_for_each(|entry| self.add_entry(entry))
+    }
 
-/// Parse the given HTML file into a `Vec` of `Entry`. IO or parsing errors may occur.
-pub fn parse<P>(file_path: &P) -> anyhow::Result<Dictionary>
-where
-    P: AsRef<Path>,
-{
-    let html = std::fs::read_to_string(&file_path)?;
-    parse_raw(html)
-}
+    /// Commit the entries added since the last commit, making them visible to queries.
+    pub fn commit(&mut self) -> Result<()> {
+        let Some(writer) = &mut self.writer else {
+            return Ok(());
+        };
+        writer
+            .commit()
+            .map_err(|e| DictionaryError::index("Couldn't commit index", e))?;
+        // The reload policy would pick the commit up eventually; reload now so it's visible
+        // to the very next query
+        self.reload()
+    }
 
-pub async fn parse_url<T: IntoUrl>(url: T) -> anyhow::Result<Dictionary> {
-    let raw_html = reqwest::get(url).await.unwrap().text().await.unwrap();
-    parse_raw(raw_html)
-}
+    /// Pick up changes another process has committed to the index since this dictionary was
+    /// opened or last reloaded, e.g. after it rebuilt an index opened with `open`. Queries
+    /// started afterwards see every commit made before the call.
+    ///
+    /// The reader also reloads by itself shortly after a commit, except on wasm, so this
+    /// mainly makes the timing certain. Either way each query keeps the snapshot of the index
+    /// it started with, so one running during a reload sees the old entries or the new ones,
+    /// never a mix; but consecutive queries, such as the pages of `search_page`, may see
+    /// different snapshots. This takes `&self`, so a dictionary shared between threads can be
+    /// reloaded while they query it.
+    pub fn reload(&self) -> Result<()> {
+        self.reader
+            .reload()
+            .map_err(|e| DictionaryError::index("Couldn't reload index", e))
+    }
 
-#[cfg(test)]
-mod test {
-    use super::parse;
-    use log::info;
-    use std::path::PathBuf;
+    /// Build the tantivy document stored for an entry.
+    fn document(fields: &Fields, entry: Entry) -> Document {
+        let mut document = doc!(
+            fields.word_folded => fold(&entry.word),
+            fields.word_exact => entry.word.clone(),
+            fields.word_key => headword_key(&entry.word),
+            fields.definition_len => entry.definition.chars().count() as u64,
+            fields.word => entry.word,
+            fields.definition => entry.definition,
+        );
+        if let Some(part_of_speech) = entry.part_of_speech {
+            document.add_text(fields.part_of_speech, part_of_speech);
+        }
+        if let Some(grammar) = entry.grammar {
+            document.add_text(fields.word_class, grammar.class.as_str());
+            if let Some(gender) = grammar.gender {
+                document.add_text(fields.gender, gender.as_str());
+            }
+            if let Some(inflection) = grammar.inflection {
+                document.add_text(fields.inflection, inflection.as_str());
+            }
+        }
+        if let Some(id) = entry.id {
+            document.add_text(fields.id, id);
+        }
+        for reference in entry.see_also {
+            document.add_text(fields.see_also, reference);
+        }
+        if let Some(original_word) = entry.original_word {
+            document.add_text(fields.original_word, original_word);
+        }
+        if let Some(ordinal) = entry.ordinal {
+            document.add_u64(fields.ordinal, ordinal);
+        }
+        for sense in entry.senses {
+            document.add_text(fields.senses, sense);
+        }
+        if let Some(source) = entry.source {
+            document.add_text(fields.source, source);
+        }
+        document
+    }
 
-    fn init() {
-        let _ = env_logger::builder().is_test(true).try_init();
+    /// Reconstruct an entry from its stored tantivy document.
+    fn entry(&self, doc: &Document) -> Result<Entry> {
+        let text = |field: Field| {
+            doc.get_first(field)
+                .and_then(|v| v.as_text())
+                .map(str::to_owned)
+        };
+        Ok(Entry {
+            word: text(self.fields.word).unwrap_or_default(),
+            definition: text(self.fields.definition).unwrap_or_default(),
+            part_of_speech: text(self.fields.part_of_speech),
+            grammar: text(self.fields.word_class)
+                .and_then(|class| WordClass::from_name(&class))
+                .map(|class| Grammar {
+                    class,
+                    gender: text(self.fields.gender).and_then(|g| Gender::from_name(&g)),
+                    inflection: text(self.fields.inflection)
+                        .and_then(|i| Inflection::from_name(&i)),
+                }),
+            id: text(self.fields.id),
+            see_also: doc
+                .get_all(self.fields.see_also)
+                .filter_map(|v| v.as_text())
+                .map(str::to_owned)
+                .collect(),
+            original_word: text(self.fields.original_word),
+            ordinal: doc.get_first(self.fields.ordinal).and_then(|v| v.as_u64()),
+            senses: doc
+                .get_all(self.fields.senses)
+                .filter_map(|v| v.as_text())
+                .map(str::to_owned)
+                .collect(),
+            source: text(self.fields.source),
+        })
     }
 
-    #[test]
-    fn test_parse() {
-        init();
-        let dictionary = 
-            parse_url("https://www.gutenberg.org/cache/epub/31543/pg31543-images.html".to_string()).unwrap();
+    /// Parse `query` with `parser` and collect the top `limit` matching entries with their
+    /// scores.
+    fn query(&self, parser: &QueryParser, query: &str, limit: usize) -> Result<Vec<(f32, Entry)>> {
+        let query = parse_query(parser, query)?;
+        self.collect(&query, limit)
+    }
 
-        let top_docs = dictionary.search("light", None).unwrap();
-        assert_eq!(10, top_docs.len());
+    /// Collect the top `limit` entries matching `query` with their scores.
+    fn collect(&self, query: &dyn Query, limit: usize) -> Result<Vec<(f32, Entry)>> {
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
+        let searcher = self.reader.searcher();
+        let top_docs = searcher
+            .search(query, &TopDocs::with_limit(limit))
+            .map_err(DictionaryError::Search)?;
+        self.entries(&searcher, top_docs)
+    }
 
-        //for (score, doc_address) in top_docs {
-        //    let retrieved_doc = searcher.doc(doc_address).unwrap();
-        //    println!("{score} {:?}", &retrieved_doc);
+    /// Collect one page of entries matching `query`, along with the total number of matches.
+    fn collect_page(&self, query: &dyn Query, page: Page) -> Result<Hits> {
+        let searcher = self.reader.searcher();
+        if page.limit == 0 {
+            let total = searcher
+                .search(query, &Count)
+                .map_err(DictionaryError::Search)?;
+            return Ok(Hits {
+                total,
+                entries: Vec::new(),
+            });
+        }
+        let (top_docs, total) = searcher
+            .search(
+                query,
+                &(
+                    TopDocs::with_limit(page.limit).and_offset(page.offset),
+                    Count,
+                ),
+            )
+            .map_err(DictionaryError::Search)?;
+        Ok(Hits {
+            total,
+            entries: unscored(self.entries(&searcher, top_docs)?),
+        })
+    }
+
+    /// Load the stored entries for the given search hits, from the searcher that found them.
+    fn entries(
+        &self,
+        searcher: &Searcher,
+        top_docs: Vec<(f32, DocAddress)>,
+    ) -> Result<Vec<(f32, Entry)>> {
+        top_docs
+            .iter()
+            .map(|(score, address)| {
+                let doc = searcher.doc(*address).map_err(DictionaryError::Search)?;
+                Ok((*score, self.entry(&doc)?))
+            })
+            .collect()
+    }
+
+    /// Every entry in the dictionary, in index order, without running a query.
+    pub fn iter_entries(&self) -> impl Iterator<Item = Result<Entry>> + '_ {
+        let searcher = self.reader.searcher();
+        let addresses = searcher
+            .segment_readers()
+            .iter()
+            .enumerate()
+            .flat_map(|(segment_ord, segment_reader)| {
+                segment_reader
+                    .doc_ids_alive()
+                    .map(move |doc_id| DocAddress::new(segment_ord as u32, doc_id))
+            })
+            .collect::<Vec<_>>();
+        addresses.into_iter().map(move |address| {
+            let doc = searcher.doc(address).map_err(DictionaryError::Search)?;
+            self.entry(&doc)
+        })
+    }
+
+    /// Every entry in the order it appeared in the source document, by `Entry::ordinal`.
+    /// Entries without an ordinal follow, in index order.
+    pub fn entries_in_source_order(&self) -> Result<Vec<Entry>> {
+        let mut entries = self.iter_entries().collect::<Result<Vec<_>>>()?;
+        sort_by_ordinal(&mut entries);
+        Ok(entries)
+    }
+
+    /// An entry chosen uniformly at random, e.g. for a word of the day.
+    pub fn random(&self) -> Result<Entry> {
+        self.random_with(&mut rand::thread_rng())
+    }
+
+    /// Like [`Dictionary::random`], but the same `seed` always picks the same entry from the
+    /// same index.
+    pub fn random_with_seed(&self, seed: u64) -> Result<Entry> {
+        self.random_with(&mut StdRng::seed_from_u64(seed))
+    }
+
+    fn random_with(&self, rng: &mut impl Rng) -> Result<Entry> {
+        let searcher = self.reader.searcher();
+        let total = searcher.num_docs();
+        if total == 0 {
+            return Err(DictionaryError::Empty);
+        }
+        let mut n = rng.gen_range(0..total);
+        for (segment_ord, segment_reader) in searcher.segment_readers().iter().enumerate() {
+            let alive = u64::from(segment_reader.num_docs());
+            if n < alive {
+                let doc_id = segment_reader
+                    .doc_ids_alive()
+                    .nth(n as usize)
+                    .expect("segment has fewer live documents than it reports");
+                let doc = searcher
+                    .doc(DocAddress::new(segment_ord as u32, doc_id))
+                    .map_err(DictionaryError::Search)?;
+                return self.entry(&doc);
+            }
+            n -= alive;
+        }
+        unreachable!("the searcher reports more documents than its segments hold")
+    }
+
+    /// Write every entry to a StarDict dictionary in `dir`, as `anglo-saxon-dictionary.ifo`,
+    /// `.idx` and `.dict`, for use in GoldenDict and other StarDict readers. Definitions are
+    /// plain UTF-8 text, prefixed with the part of speech where known.
+    #[cfg(feature = "fs")]
+    pub fn export_stardict<P: AsRef<Path>>(&self, dir: P) -> Result<()> {
+        stardict::write(dir.as_ref(), self.iter_entries())
+    }
+
+    /// Write every entry as JSON Lines in source order, after a line holding the
+    /// `ExportHeader`, for reading back with `from_json_lines`.
+    #[cfg(feature = "serde")]
+    pub fn export_json_lines<W: std::io::Write>(&self, writer: W) -> Result<()> {
+        json::write_json_lines(writer, &self.entries_in_source_order()?)
+    }
+
+    /// Write every entry in source order as one JSON object: the `ExportHeader`'s fields
+    /// with the entries in an `entries` array, for reading back with `from_json`.
+    #[cfg(feature = "serde")]
+    pub fn export_json<W: std::io::Write>(&self, writer: W) -> Result<()> {
+        json::write_json(writer, &self.entries_in_source_order()?)
+    }
+
+    /// Write each distinct headword on its own line, in Old English alphabetical order, e.g.
+    /// as a word list for a spellchecker. With `normalize`, headwords are folded first, as by
+    /// [`fold`], so "ǣ" and "æ" are both written "ae"; words that fold the same are written
+    /// once.
+    pub fn export_wordlist<W: std::io::Write>(&self, mut writer: W, normalize: bool) -> Result<()> {
+        let mut words = BTreeSet::new();
+        for entry in self.iter_entries() {
+            let word = entry?.word;
+            words.insert(if normalize { fold(&word) } else { word });
+        }
+        let mut words: Vec<_> = words.into_iter().collect();
+        words.sort_by(|a, b| compare_old_english(a, b));
+        for word in words {
+            writeln!(writer, "{}", word)
+                .map_err(|e| DictionaryError::io("Couldn't write word list", e))?;
+        }
+        Ok(())
+    }
+
+    /// Write every entry to an `entries(word, definition, part_of_speech, id)` table in the
+    /// SQLite database at `path`, indexed on `word`. The file is created if needed, but must
+    /// not already have an `entries` table.
+    #[cfg(feature = "sqlite")]
+    pub fn export_sqlite<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        sqlite::write(path.as_ref(), self.iter_entries())
+    }
+
+    /// Find entries whose headword or definition matches `query`, which may use the query
+    /// parser syntax described at [`Dictionary::search_raw`]. A valid query matching nothing
+    /// gives an empty `Vec`; only a malformed one is an error.
+    pub fn search(&self, query: &str, limit: Option<usize>) -> Result<Vec<Entry>> {
+        Ok(unscored(self.search_scored(query, limit)?))
+    }
+
+    /// The entry parsed from the paragraph with the given `word_` anchor id, e.g. "word_1234",
+    /// for following links into the source page. A leading `#`, as in a link fragment, is
+    /// ignored. Paragraphs with several headwords give several entries with one id; the first
+    /// is returned. `None` if no entry has the id.
+    pub fn get_by_id(&self, id: &str) -> Result<Option<Entry>> {
+        let term = Term::from_field_text(self.fields.id, id.strip_prefix('#').unwrap_or(id));
+        let query = TermQuery::new(term, IndexRecordOption::Basic);
+        Ok(unscored(self.collect(&query, 1)?).into_iter().next())
+    }
+
+    /// Like `search`, matching definitions only, to find the Old English words glossed by an
+    /// English term, e.g. every word for "warrior", without headwords that happen to match.
+    pub fn search_definition(&self, query: &str, limit: Option<usize>) -> Result<Vec<Entry>> {
+        Ok(unscored(self.query(
+            &self.query_parsers.definition,
+            query,
+            limit.unwrap_or(10),
+        )?))
+    }
+
+    /// Like `search`, keeping only entries whose part of speech is exactly `pos`, e.g. "m."
+    /// for masculine nouns. A part of speech no entry has matches nothing.
+    pub fn search_with_pos(
+        &self,
+        query: &str,
+        pos: Option<&str>,
+        limit: Option<usize>,
+    ) -> Result<Vec<Entry>> {
+        let query = parse_query(&self.query_parsers.all, query)?;
+        let query = self.with_pos(query, pos);
+        Ok(unscored(self.collect(&*query, limit.unwrap_or(10))?))
+    }
+
+    /// Like `search`, keeping only entries labelled with `source` when they were parsed, to
+    /// pick one edition's glosses out of a merged dictionary.
+    pub fn search_with_source(
+        &self,
+        query: &str,
+        source: Option<&str>,
+        limit: Option<usize>,
+    ) -> Result<Vec<Entry>> {
+        let query = parse_query(&self.query_parsers.all, query)?;
+        let query = self.restrict(query, self.fields.source, source);
+        Ok(unscored(self.collect(&*query, limit.unwrap_or(10))?))
+    }
+
+    /// Like `define`, keeping only entries whose part of speech is exactly `pos`.
+    pub fn define_with_pos(
+        &self,
+        word: &str,
+        pos: Option<&str>,
+        limit: Option<usize>,
+    ) -> Result<Vec<Entry>> {
+        let term = Term::from_field_text(self.fields.word_key, &headword_key(word));
+        let query = self.with_pos(
+            Box::new(TermQuery::new(term, IndexRecordOption::Basic)),
+            pos,
+        );
+        Ok(unscored(self.collect(&*query, limit.unwrap_or(10))?))
+    }
+
+    /// Restrict `query` to entries with the part of speech `pos`, if one is given.
+    fn with_pos(&self, query: Box<dyn Query>, pos: Option<&str>) -> Box<dyn Query> {
+        self.restrict(query, self.fields.part_of_speech, pos)
+    }
+
+    /// Narrow `query` to entries whose `field` is exactly `value`, if given.
+    fn restrict(&self, query: Box<dyn Query>, field: Field, value: Option<&str>) -> Box<dyn Query> {
+        let Some(value) = value else {
+            return query;
+        };
+        let term = Term::from_field_text(field, value);
+        Box::new(BooleanQuery::new(vec![
+            (Occur::Must, query),
+            (
+                Occur::Must,
+                Box::new(TermQuery::new(term, IndexRecordOption::Basic)),
+            ),
+        ]))
+    }
+
+    /// Up to `limit` distinct headwords beginning with `prefix`, in alphabetical order, for
+    /// type-ahead completion. These are read from the index's term dictionary, so the match
+    /// is exact: case and diacritics must agree.
+    pub fn suggest(&self, prefix: &str, limit: usize) -> Result<Vec<String>> {
+        let field = self.fields.word_exact;
+        let mut words = BTreeSet::new();
+        for segment_reader in self.reader.searcher().segment_readers() {
+            let inverted_index = segment_reader
+                .inverted_index(field)
+                .map_err(DictionaryError::Search)?;
+            let mut terms = inverted_index
+                .terms()
+                .range()
+                .ge(prefix)
+                .into_stream()
+                .map_err(|e| DictionaryError::Search(e.into()))?;
+            // Each segment's terms are sorted, so its first `limit` matches are all that can
+            // make the overall first `limit`
+            let mut taken = 0;
+            while taken < limit && terms.advance() {
+                let Ok(word) = std::str::from_utf8(terms.key()) else {
+                    continue;
+                };
+                if !word.starts_with(prefix) {
+                    break;
+                }
+                words.insert(word.to_owned());
+                taken += 1;
+            }
+        }
+        Ok(words.into_iter().take(limit).collect())
+    }
+
+    /// Every entry whose headword begins with `prefix`, in Old English alphabetical order, for
+    /// browsing the dictionary by letter. Case is ignored, and þ and ð, being one letter,
+    /// match each other, but other letters must match exactly: "a" doesn't find "æ" or "ā".
+    pub fn words_starting_with(&self, prefix: &str) -> Result<Vec<Entry>> {
+        self.words_matching(&format!("(?i){}.*", headword_pattern(prefix)))
+    }
+
+    /// Every entry whose headword contains `substring` anywhere, in Old English alphabetical
+    /// order, for when only the middle of a word comes to mind. Letters match as in
+    /// `words_starting_with`. A substring shorter than two letters matches most of the
+    /// dictionary, so it's logged as a warning, though every match is still returned.
+    pub fn words_containing(&self, substring: &str) -> Result<Vec<Entry>> {
+        if substring.chars().count() < MIN_SUBSTRING_LEN {
+            warn!(
+                "Substring {:?} is shorter than {} letters and will match most headwords",
+                substring, MIN_SUBSTRING_LEN
+            );
+        }
+        self.words_matching(&format!("(?i).*{}.*", headword_pattern(substring)))
+    }
+
+    /// Every entry whose exact headword matches the regular expression `pattern`, in Old
+    /// English alphabetical order.
+    fn words_matching(&self, pattern: &str) -> Result<Vec<Entry>> {
+        let query = RegexQuery::from_pattern(pattern, self.fields.word_exact)
+            .map_err(|e| DictionaryError::InvalidQuery(e.to_string()))?;
+        let limit = self.len() as usize;
+        let mut entries = unscored(self.collect(&query, limit)?);
+        entries.sort_by(|a, b| compare_old_english(&a.word, &b.word));
+        Ok(entries)
+    }
+
+    /// Every entry whose definition is between `min` and `max` characters long, inclusive,
+    /// shortest first, e.g. to find stub entries the parser may have cut short. Length counts
+    /// Unicode scalar values rather than bytes, so "æ" and "ā" count once each.
+    pub fn entries_with_length(&self, min: usize, max: usize) -> Result<Vec<Entry>> {
+        let query = RangeQuery::new_u64_bounds(
+            "definition_len".to_owned(),
+            Bound::Included(min as u64),
+            Bound::Included(max as u64),
+        );
+        let limit = self.len() as usize;
+        let mut entries = unscored(self.collect(&query, limit)?);
+        entries.sort_by(|a, b| {
+            a.definition
+                .chars()
+                .count()
+                .cmp(&b.definition.chars().count())
+                .then_with(|| compare_old_english(&a.word, &b.word))
+        });
+        Ok(entries)
+    }
+
+    /// The number of entries in the dictionary, from the index's segment metadata rather than
+    /// a query.
+    pub fn len(&self) -> u64 {
+        self.reader.searcher().num_docs()
+    }
+
+    /// Whether the dictionary has no entries at all.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The number of entries `search` would find for `query` with no limit, without loading
+    /// any of them.
+    pub fn count(&self, query: &str) -> Result<usize> {
+        let query = parse_query(&self.query_parsers.all, query)?;
+        self.reader
+            .searcher()
+            .search(&query, &Count)
+            .map_err(DictionaryError::Search)
+    }
+
+    /// Like `search`, with the results in the given order. Sorting alphabetically orders the
+    /// `limit` best matches; it doesn't change which entries are returned.
+    pub fn search_sorted(
+        &self,
+        query: &str,
+        limit: Option<usize>,
+        sort: SortOrder,
+    ) -> Result<Vec<Entry>> {
+        let mut entries = self.search(query, limit)?;
+        match sort {
+            SortOrder::Relevance => {}
+            SortOrder::AlphabeticalOE => {
+                entries.sort_by(|a, b| compare_old_english(&a.word, &b.word))
+            }
+            SortOrder::Source => sort_by_ordinal(&mut entries),
+        }
+        Ok(entries)
+    }
+
+    /// Pass `query` straight to tantivy's query parser over the `word` and `definition`
+    /// fields. Beyond bare terms, which match either field, this accepts:
+    ///
+    /// * field-qualified terms, e.g. `word:cyning` or `definition:king`
+    /// * phrases, e.g. `definition:"king of the"`, and phrase prefixes, e.g.
+    ///   `definition:"king of th"*`
+    /// * single-word prefixes, e.g. `word:cyn*`, matching headwords as `words_starting_with`
+    ///   does. Unqualified, as in `cyn*`, they match either field. They can't be combined
+    ///   with `AND`, `OR` or parentheses, which is a [`DictionaryError::InvalidQuery`] error
+    /// * `+required` and `-excluded` terms, `AND`/`OR` and parentheses
+    ///
+    /// `part_of_speech` and `id` may be qualified too, matching their whole value, e.g.
+    /// `part_of_speech:m.`, as may the components of `Entry::grammar`, e.g. `gender:feminine`,
+    /// `word_class:verb` or `inflection:weak`. Malformed syntax is a
+    /// [`DictionaryError::QueryParse`] error. Methods taking a `word` or `term` rather than a
+    /// `query` treat it literally.
+    pub fn search_raw(&self, query: &str, limit: Option<usize>) -> Result<Vec<Entry>> {
+        let limit = limit.unwrap_or(10);
+        // tantivy's query grammar only takes prefixes of phrases, so single words are taken
+        // out of the query and matched here
+        let Some(clauses) = prefix_query::clauses(query)? else {
+            return Ok(unscored(self.query(
+                &self.query_parsers.all,
+                query,
+                limit,
+            )?));
+        };
+        let clauses = clauses
+            .into_iter()
+            .map(|clause| match clause {
+                prefix_query::Clause::Prefix {
+                    occur,
+                    field,
+                    prefix,
+                } => Ok((occur, self.prefix_query(field, prefix, query)?)),
+                prefix_query::Clause::Other { occur, text } => {
+                    Ok((occur, parse_query(&self.query_parsers.all, text)?))
+                }
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if clauses.iter().all(|(occur, _)| *occur == Occur::MustNot) {
+            return Err(query_error::error(
+                QueryParserError::AllButQueryForbidden,
+                query,
+            ));
+        }
+        Ok(unscored(self.collect(&BooleanQuery::new(clauses), limit)?))
+    }
+
+    /// Match the terms of the field named `field` starting with `prefix`, or of the headword
+    /// and definition when no field is named.
+    fn prefix_query(
+        &self,
+        field: Option<&str>,
+        prefix: &str,
+        query: &str,
+    ) -> Result<Box<dyn Query>> {
+        let regex = |field: Field, pattern: &str| -> Result<Box<dyn Query>> {
+            let query = RegexQuery::from_pattern(pattern, field)
+                .map_err(|e| DictionaryError::InvalidQuery(e.to_string()))?;
+            Ok(Box::new(query))
+        };
+        let name = match field {
+            None => {
+                return Ok(Box::new(BooleanQuery::union(vec![
+                    self.prefix_query(Some("word"), prefix, query)?,
+                    self.prefix_query(Some("definition"), prefix, query)?,
+                ])))
+            }
+            Some("word") => {
+                let pattern = format!("(?i){}.*", headword_pattern(prefix));
+                return regex(self.fields.word_exact, &pattern);
+            }
+            Some(name) => name,
+        };
+        let field = self.index.schema().get_field(name).map_err(|_| {
+            query_error::error(QueryParserError::FieldDoesNotExist(name.to_owned()), query)
+        })?;
+        // The prefix is matched against terms as they were indexed, i.e. once tokenized
+        let mut analyzer = self.index.tokenizer_for_field(field).map_err(|_| {
+            DictionaryError::InvalidQuery(format!("field '{}' can't be searched by prefix", name))
+        })?;
+        let mut terms = Vec::new();
+        analyzer
+            .token_stream(prefix)
+            .process(&mut |token| terms.push(token.text.clone()));
+        match terms.as_slice() {
+            [term] => regex(field, &format!("{}.*", regex_syntax::escape(term))),
+            _ => Err(DictionaryError::InvalidQuery(format!(
+                "the prefix {:?} isn't a single word; quote it for a phrase prefix",
+                prefix
+            ))),
+        }
+    }
+
+    /// Why `search` scored the entry for `word` as it did against `query`: tantivy's
+    /// explanation of the BM25 factors and boosts involved, as pretty-printed JSON. A debugging
+    /// aid for tuning boosts. Of several entries for `word`, the best scoring is explained.
+    pub fn explain(&self, query: &str, word: &str) -> Result<String> {
+        let query = parse_query(&self.query_parsers.all, query)?;
+        let headword = Term::from_field_text(self.fields.word_key, &headword_key(word));
+        let for_word = BooleanQuery::new(vec![
+            (Occur::Must, query.box_clone()),
+            (
+                Occur::Must,
+                Box::new(TermQuery::new(headword, IndexRecordOption::Basic)),
+            ),
+        ]);
+        let searcher = self.reader.searcher();
+        let top_docs = searcher
+            .search(&for_word, &TopDocs::with_limit(1))
+            .map_err(DictionaryError::Search)?;
+        let Some((_, address)) = top_docs.first() else {
+            return Err(DictionaryError::NoMatch(word.to_owned()));
+        };
+        let explanation = query
+            .explain(&searcher, *address)
+            .map_err(DictionaryError::Search)?;
+        Ok(explanation.to_pretty_json())
+    }
+
+    /// Like `search`, but each entry is paired with its BM25 relevance score.
+    pub fn search_scored(&self, query: &str, limit: Option<usize>) -> Result<Vec<(f32, Entry)>> {
+        self.query(&self.query_parsers.all, query, limit.unwrap_or(10))
+    }
+
+    /// Like `search`, but each entry is paired with a snippet of its definition around the
+    /// matched terms. The snippet is empty when only the headword matched.
+    pub fn search_snippets(
+        &self,
+        query: &str,
+        limit: Option<usize>,
+    ) -> Result<Vec<(Entry, Snippet)>> {
+        let query = parse_query(&self.query_parsers.all, query)?;
+        let limit = limit.unwrap_or(10);
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
+        let searcher = self.reader.searcher();
+        let generator = SnippetGenerator::create(&searcher, &*query, self.fields.definition)
+            .map_err(DictionaryError::Search)?;
+        let top_docs = searcher
+            .search(&query, &TopDocs::with_limit(limit))
+            .map_err(DictionaryError::Search)?;
+        top_docs
+            .into_iter()
+            .map(|(_, address)| {
+                let doc = searcher.doc(address).map_err(DictionaryError::Search)?;
+                let snippet = generator.snippet_from_doc(&doc);
+                let snippet = Snippet {
+                    fragment: snippet.fragment().to_owned(),
+                    highlighted: snippet.highlighted().to_vec(),
+                };
+                Ok((self.entry(&doc)?, snippet))
+            })
+            .collect()
+    }
+
+    /// Look up the entries whose headword is `word`, ignoring case and whether "æ" is written
+    /// as "ae", so "Æsc", "æsc" and "AESC" all find "æsc". Other diacritics must agree.
+    pub fn define(&self, word: &str, limit: Option<usize>) -> Result<Vec<Entry>> {
+        let term = Term::from_field_text(self.fields.word_key, &headword_key(word));
+        let query = TermQuery::new(term, IndexRecordOption::Basic);
+        Ok(unscored(self.collect(&query, limit.unwrap_or(10))?))
+    }
+
+    /// Every entry whose headword is any of `words`, matched as `define` does, in a single
+    /// query, e.g. to try each known spelling of a word at once. Entries found through more
+    /// than one of the words are returned once. Homographs sharing a paragraph, and so its
+    /// `id`, are each returned, so every spelling asked for that's defined is among them.
+    pub fn define_any(&self, words: &[&str]) -> Result<Vec<Entry>> {
+        let clauses = words
+            .iter()
+            .map(|word| {
+                let term = Term::from_field_text(self.fields.word_key, &headword_key(word));
+                let query: Box<dyn Query> =
+                    Box::new(TermQuery::new(term, IndexRecordOption::Basic));
+                (Occur::Should, query)
+            })
+            .collect::<Vec<_>>();
+        if clauses.is_empty() {
+            return Ok(Vec::new());
+        }
+        let query = BooleanQuery::new(clauses);
+        Ok(dedup(unscored(self.collect(&query, self.len() as usize)?)))
+    }
+
+    /// The best of the entries `define` finds for `word`, or `None` when there are none.
+    pub fn define_one(&self, word: &str) -> Result<Option<Entry>> {
+        Ok(self.define(word, Some(1))?.into_iter().next())
+    }
+
+    /// Look up the entries whose headword is exactly `word`, including its case and
+    /// diacritics.
+    pub fn define_exact(&self, word: &str, limit: Option<usize>) -> Result<Vec<Entry>> {
+        let term = Term::from_field_text(self.fields.word_exact, word);
+        let query = TermQuery::new(term, IndexRecordOption::Basic);
+        Ok(unscored(self.collect(&query, limit.unwrap_or(10))?))
+    }
+
+    /// Look up headwords matching `query` in the query parser syntax, so any headword
+    /// containing a query term is returned.
+    pub fn define_loose(&self, query: &str, limit: Option<usize>) -> Result<Vec<Entry>> {
+        Ok(unscored(self.query(
+            &self.query_parsers.word,
+            query,
+            limit.unwrap_or(10),
+        )?))
+    }
+
+    /// Like `search`, but returns the given page of results along with the total hit count.
+    pub fn search_page(&self, query: &str, page: Page) -> Result<Hits> {
+        let query = parse_query(&self.query_parsers.all, query)?;
+        self.collect_page(&query, page)
+    }
+
+    /// Like `define`, but returns the given page of results along with the total hit count.
+    pub fn define_page(&self, word: &str, page: Page) -> Result<Hits> {
+        let term = Term::from_field_text(self.fields.word_key, &headword_key(word));
+        self.collect_page(&TermQuery::new(term, IndexRecordOption::Basic), page)
+    }
+
+    /// Find headwords within `distance` edits of `term`, so misspellings like "cyninge" still
+    /// find "cyning". The distance may be at most 2.
+    pub fn search_fuzzy(
+        &self,
+        term: &str,
+        distance: u8,
+        limit: Option<usize>,
+    ) -> Result<Vec<Entry>> {
+        if distance > 2 {
+            return Err(DictionaryError::InvalidQuery(format!(
+                "fuzzy distance must be at most 2, got {}",
+                distance
+            )));
+        }
+        let term = Term::from_field_text(self.fields.word, &term.to_lowercase());
+        let query = FuzzyTermQuery::new(term, distance, true);
+        Ok(unscored(self.collect(&query, limit.unwrap_or(10))?))
+    }
+
+    /// A forgiving lookup of `term`: the entries `define` finds, then those whose headword is
+    /// a few edits away, as `search_fuzzy` finds them, up to `limit` in all. Terms of up to
+    /// four letters allow one edit and longer ones two, so short terms don't match most
+    /// of the dictionary. A headword found exactly isn't repeated among the fuzzy matches.
+    pub fn lookup(&self, term: &str, limit: Option<usize>) -> Result<Vec<(MatchKind, Entry)>> {
+        let limit = limit.unwrap_or(10);
+        let exact = self.define(term, Some(limit))?;
+        let words: HashSet<String> = exact.iter().map(|e| headword_key(&e.word)).collect();
+        let distance = if term.chars().count() <= 4 { 1 } else { 2 };
+        // Ask for enough to make up the limit after leaving out the exact matches
+        let fuzzy = self
+            .search_fuzzy(term, distance, Some(limit + exact.len()))?
+            .into_iter()
+            .filter(|entry| !words.contains(&headword_key(&entry.word)));
+        Ok(exact
+            .into_iter()
+            .map(|entry| (MatchKind::Exact, entry))
+            .chain(fuzzy.map(|entry| (MatchKind::Fuzzy, entry)))
+            .take(limit)
+            .collect())
+    }
+
+    /// Headwords close to `word`, for suggesting alternatives when `define` finds nothing:
+    /// up to `limit` distinct headwords one edit away, or two edits if none are one away.
+    pub fn did_you_mean(&self, word: &str, limit: usize) -> Result<Vec<String>> {
+        for distance in 1..=2 {
+            // Ask for extra entries, as homographs share a headword
+            let mut words: Vec<String> = Vec::new();
+            for entry in self.search_fuzzy(word, distance, Some(limit * 4))? {
+                if entry.word != word && !words.contains(&entry.word) {
+                    words.push(entry.word);
+                }
+            }
+            if !words.is_empty() {
+                words.truncate(limit);
+                return Ok(words);
+            }
+        }
+        Ok(Vec::new())
+    }
+
+    /// Like `search`, but headwords are matched ignoring macrons and other diacritics, with
+    /// "æ" matching "ae" and "þ"/"ð" matching "th". See [`fold`].
+    pub fn search_folded(&self, query: &str, limit: Option<usize>) -> Result<Vec<Entry>> {
+        Ok(unscored(self.query(
+            &self.query_parsers.folded,
+            &fold(query),
+            limit.unwrap_or(10),
+        )?))
+    }
+
+    /// Like `define`, but headwords are matched ignoring macrons and other diacritics, so
+    /// "god" finds both "god" and "gōd". See [`fold`].
+    pub fn define_folded(&self, query: &str, limit: Option<usize>) -> Result<Vec<Entry>> {
+        Ok(unscored(self.query(
+            &self.query_parsers.word_folded,
+            &fold(query),
+            limit.unwrap_or(10),
+        )?))
+    }
+}
+
+/// Drop the scores from scored results.
+fn unscored(results: Vec<(f32, Entry)>) -> Vec<Entry> {
+    results.into_iter().map(|(_, entry)| entry).collect()
+}
+
+impl TryFrom<Vec<Entry>> for Dictionary {
+    type Error = DictionaryError;
+
+    fn try_from(entries: Vec<Entry>) -> Result<Self> {
+        Dictionary::new(entries)
+    }
+}
+
+/// What parsing a document produced, to judge how well a scrape went.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ParseReport {
+    /// The paragraphs that looked like entries, whether converted or skipped.
+    pub total_paragraphs: usize,
+    /// The entries those paragraphs produced. A paragraph may hold several.
+    pub entries: usize,
+    /// The paragraphs skipped as malformed.
+    pub skipped: usize,
+    /// Why each skipped paragraph was skipped, in document order.
+    pub warnings: Vec<String>,
+}
+
+/// A lazy iterator over the entries of a dictionary HTML document, yielding an error for each
+/// paragraph that couldn't be converted. The document is parsed up front, but entries are
+/// only built as the iterator advances.
+pub struct Entries {
+    document: Html,
+    paragraphs: std::vec::IntoIter<NodeId>,
+    word_selector: Option<Selector>,
+    options: ParseOptions,
+    pending: std::vec::IntoIter<Entry>,
+    error: Option<DictionaryError>,
+}
+
+impl Entries {
+    /// Select the dictionary paragraphs of the given HTML.
+    pub fn from_html(html: &str) -> Self {
+        let document = Html::parse_document(html);
+        let paragraphs = Selector::parse("p").unwrap();
+        let paragraphs: Vec<NodeId> = document
+            .select(&paragraphs)
+            .filter(is_entry_paragraph)
+            .map(|n| n.id())
+            .collect();
+
+        Entries {
+            document,
+            paragraphs: paragraphs.into_iter(),
+            word_selector: None,
+            options: ParseOptions::default(),
+            pending: Vec::new().into_iter(),
+            error: None,
+        }
+    }
+
+    /// Select the entries of HTML from another source: each element matching `entry_selector`
+    /// holds one entry, whose headwords are the elements within it matching `word_selector`.
+    /// See [`Entry::from_element`] for how the definition is found.
+    pub fn with_selector(html: &str, entry_selector: &str, word_selector: &str) -> Result<Self> {
+        let entry_selector = parse_selector(entry_selector)?;
+        let word_selector = parse_selector(word_selector)?;
+        let document = Html::parse_document(html);
+        let paragraphs: Vec<NodeId> = document.select(&entry_selector).map(|n| n.id()).collect();
+
+        Ok(Entries {
+            document,
+            paragraphs: paragraphs.into_iter(),
+            word_selector: Some(word_selector),
+            options: ParseOptions::default(),
+            pending: Vec::new().into_iter(),
+            error: None,
+        })
+    }
+
+    /// Convert entries with the given options. `markdown` has no effect on entries chosen with
+    /// `with_selector`.
+    pub fn options(mut self, options: ParseOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    #[cfg(feature = "fs")]
+    fn from_error(error: DictionaryError) -> Self {
+        Entries {
+            error: Some(error),
+            ..Entries::from_html("")
+        }
+    }
+}
+
+impl Iterator for Entries {
+    type Item = Result<Entry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(error) = self.error.take() {
+            return Some(Err(error));
+        }
+        loop {
+            if let Some(entry) = self.pending.next() {
+                return Some(Ok(entry));
+            }
+            let id = self.paragraphs.next()?;
+            let paragraph = self.document.tree.get(id).and_then(ElementRef::wrap)?;
+            let entries = match &self.word_selector {
+                Some(word_selector) => Entry::from_element(paragraph, word_selector)
+                    .map(|entries| {
+                        entries
+                            .into_iter()
+                            .map(|entry| entry.normalize(&self.options))
+                            .collect()
+                    })
+                    .and_then(|entries| require_definition(entries, &self.options)),
+                None => Entry::from_paragraph_with_options(paragraph, &self.options),
+            };
+            match entries {
+                Ok(entries) => self.pending = entries.into_iter(),
+                Err(e) => {
+                    debug!("Couldn't convert paragraph {:?}", paragraph.html());
+                    return Some(Err(e));
+                }
+            }
+        }
+    }
+}
+
+/// Drop entries that repeat an earlier one's `id` and word, or its word and definition when
+/// neither has an `id`, keeping the first. The headwords of one paragraph share its `id`, so
+/// the word is part of the key for every spelling to be kept.
+fn dedup(entries: Vec<Entry>) -> Vec<Entry> {
+    let mut seen = HashSet::new();
+    entries
+        .into_iter()
+        .filter(|entry| {
+            let definition = match &entry.id {
+                Some(_) => None,
+                None => Some(entry.definition.clone()),
+            };
+            seen.insert((entry.id.clone(), entry.word.clone(), definition))
+        })
+        .collect()
+}
+
+fn parse_selector(selector: &str) -> Result<Selector> {
+    Selector::parse(selector).map_err(|_| DictionaryError::InvalidSelector(selector.to_owned()))
+}
+
+/// Collect the entries of a document, skipping malformed paragraphs.
+fn collect_entries(entries: impl IntoIterator<Item = Result<Entry>>) -> Result<Vec<Entry>> {
+    Ok(collect_with_report(entries, 0)?.0)
+}
+
+/// Like `collect_entries`, also counting the entries and skipped paragraphs out of
+/// `total_paragraphs`.
+fn collect_with_report(
+    entries: impl IntoIterator<Item = Result<Entry>>,
+    total_paragraphs: usize,
+) -> Result<(Vec<Entry>, ParseReport)> {
+    let mut report = ParseReport {
+        total_paragraphs,
+        ..ParseReport::default()
+    };
+    let mut collected = Vec::new();
+    for entry in entries {
+        match entry {
+            Ok(mut entry) => {
+                entry.ordinal = Some(collected.len() as u64);
+                collected.push(entry);
+            }
+            Err(DictionaryError::Parse(e)) => {
+                warn!("Skipping paragraph: {}", e);
+                report.skipped += 1;
+                report.warnings.push(e);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    report.entries = collected.len();
+    Ok((collected, report))
+}
+
+/// A regular expression matching `text` literally within a headword, except that þ and ð
+/// match each other.
+fn headword_pattern(text: &str) -> String {
+    let mut pattern = String::new();
+    for c in text.chars() {
+        match c {
+            'þ' | 'ð' | 'Þ' | 'Ð' => pattern.push_str("[þðÞÐ]"),
+            c => pattern.push_str(&regex_syntax::escape(c.encode_utf8(&mut [0; 4]))),
+        }
+    }
+    pattern
+}
+
+/// Sort entries into source order, those without an ordinal last.
+pub fn sort_by_ordinal(entries: &mut [Entry]) {
+    entries.sort_by_key(|entry| (entry.ordinal.is_none(), entry.ordinal));
+}
+
+/// Select the dictionary paragraphs from an HTML document and convert them to entries.
+pub fn entries_from_html(html: &str) -> Result<Vec<Entry>> {
+    Ok(convert(Entries::from_html(html), &mut |_| {})?.0)
+}
+
+/// Like `entries_from_html`, along with counts of the paragraphs converted and skipped.
+pub fn entries_from_html_with_report(html: &str) -> Result<(Vec<Entry>, ParseReport)> {
+    convert(Entries::from_html(html), &mut |_| {})
+}
+
+/// Like `entries_from_html_with_report`, calling `on_progress` as paragraphs are converted.
+/// With the `rayon` feature, progress is only reported once conversion is complete.
+pub fn entries_from_html_with_progress(
+    html: &str,
+    mut on_progress: impl FnMut(Progress),
+) -> Result<(Vec<Entry>, ParseReport)> {
+    convert(Entries::from_html(html), &mut on_progress)
+}
+
+/// Convert every paragraph of `entries`, skipping those that aren't entries.
+#[cfg(not(feature = "rayon"))]
+fn convert(
+    entries: Entries,
+    on_progress: &mut dyn FnMut(Progress),
+) -> Result<(Vec<Entry>, ParseReport)> {
+    convert_each(entries, on_progress)
+}
+
+/// Convert the paragraphs of `entries` one after another, reporting each one converted.
+fn convert_each(
+    mut entries: Entries,
+    on_progress: &mut dyn FnMut(Progress),
+) -> Result<(Vec<Entry>, ParseReport)> {
+    let total = entries.paragraphs.len();
+    let mut done = 0;
+    let each = std::iter::from_fn(|| {
+        let entry = entries.next();
+        let converted = total - entries.paragraphs.len();
+        if converted > done {
+            done = converted;
+            on_progress(Progress::Parsed { done, total });
+        }
+        entry
+    });
+    collect_with_report(each, total)
+}
+
+/// Convert every paragraph of `entries` on the rayon thread pool. The parsed document isn't
+/// `Send`, so each paragraph is serialized and re-parsed as its own fragment; this only pays
+/// off with several cores to spread over.
+#[cfg(feature = "rayon")]
+fn convert(
+    entries: Entries,
+    on_progress: &mut dyn FnMut(Progress),
+) -> Result<(Vec<Entry>, ParseReport)> {
+    use rayon::prelude::*;
+
+    let total_paragraphs = entries.paragraphs.len();
+    if entries.word_selector.is_some() {
+        return convert_each(entries, on_progress);
+    }
+    let fragments: Vec<String> = entries
+        .paragraphs
+        .as_slice()
+        .iter()
+        .filter_map(|id| entries.document.tree.get(*id).and_then(ElementRef::wrap))
+        .map(|paragraph| {
+            // Keep the continuations alongside so the fragment still has them as siblings
+            std::iter::once(paragraph)
+                .chain(continuations(paragraph))
+                .map(|p| p.html())
+                .collect()
+        })
+        .collect();
+    let converted: Vec<Result<Vec<Entry>>> = fragments
+        .par_iter()
+        .map(|fragment| {
+            let fragment = Html::parse_fragment(fragment);
+            let paragraph = fragment
+                .select(&Selector::parse("p").unwrap())
+                .next()
+                .ok_or_else(|| DictionaryError::Parse("Paragraph went missing".to_owned()))?;
+            Entry::from_paragraph_with_options(paragraph, &entries.options)
+        })
+        .collect();
+    on_progress(Progress::Parsed {
+        done: total_paragraphs,
+        total: total_paragraphs,
+    });
+
+    collect_with_report(
+        converted.into_iter().flat_map(|entries| match entries {
+            Ok(entries) => entries.into_iter().map(Ok).collect(),
+            Err(e) => vec![Err(e)],
+        }),
+        total_paragraphs,
+    )
+}
+
+/// Parse the given in-memory HTML into a `Dictionary`.
+pub fn parse_str(html: &str) -> Result<Dictionary> {
+    entries_from_html(html)?.try_into()
+}
+
+/// Like `parse_str`, along with counts of the paragraphs converted and skipped.
+pub fn parse_str_with_report(html: &str) -> Result<(Dictionary, ParseReport)> {
+    let (entries, report) = entries_from_html_with_report(html)?;
+    Ok((entries.try_into()?, report))
+}
+
+/// Like `parse_str`, with control over how entries are extracted.
+pub fn parse_str_with_options(html: &str, options: &ParseOptions) -> Result<Dictionary> {
+    convert(
+        Entries::from_html(html).options(options.clone()),
+        &mut |_| {},
+    )?
+    .0
+    .try_into()
+}
+
+/// Explain what parsing `html` makes of each paragraph mentioning `word`: which became
+/// entries and what they hold, and why the others were skipped, e.g. to find out why a word
+/// is missing. The report prints readably with `{}`.
+pub fn parse_debug_word_str(html: &str, word: &str) -> WordDiagnostic {
+    diagnostic::diagnose(html, word, &ParseOptions::default())
+}
+
+pub fn parse_raw(html: String) -> Result<Dictionary> {
+    parse_str(&html)
+}
+
+/// Lazily parse the entries of the given HTML file, for filtering or storing them without
+/// building an index. A read error is yielded as the only item.
+#[cfg(feature = "fs")]
+pub fn parse_entries<P>(file_path: &P) -> Entries
+where
+    P: AsRef<Path>,
+{
+    match read_html(file_path.as_ref()) {
+        Ok(html) => Entries::from_html(&html),
+        Err(e) => Entries::from_error(e),
+    }
+}
+
+/// Read the HTML file at `path`, decompressing it if it's gzipped.
+#[cfg(feature = "fs")]
+pub fn read_html(path: &Path) -> Result<String> {
+    let source = path.display().to_string();
+    let bytes = std::fs::read(path)
+        .map_err(|e| DictionaryError::io(format!("Couldn't read {}", source), e))?;
+    let gzipped = path.extension().is_some_and(|ext| ext == "gz");
+    decode_html(bytes, gzipped, &source).map(Cow::into_owned)
+}
+
+/// Decode dictionary HTML held in memory, e.g. embedded with `include_bytes!`, decompressing
+/// it first if it's gzipped.
+#[cfg(feature = "fs")]
+pub fn html_from_bytes(bytes: Vec<u8>) -> Result<String> {
+    decode_html(bytes, false, "dictionary HTML").map(Cow::into_owned)
+}
+
+/// Turn raw HTML bytes into a string, decompressing them first when `gzipped` is set or they
+/// start with the gzip magic number. Uncompressed UTF-8 is only checked, not copied; other
+/// encodings are transcoded, as described at [`parse_bytes`].
+#[cfg(feature = "fs")]
+pub(crate) fn decode_html<'a>(
+    bytes: impl Into<Cow<'a, [u8]>>,
+    gzipped: bool,
+    source: &str,
+) -> Result<Cow<'a, str>> {
+    use std::io::Read;
+
+    let bytes = bytes.into();
+    let bytes = if gzipped || bytes.starts_with(&[0x1f, 0x8b]) {
+        let mut decompressed = Vec::new();
+        flate2::read::GzDecoder::new(&bytes[..])
+            .read_to_end(&mut decompressed)
+            .map_err(|e| DictionaryError::io(format!("Couldn't decompress {}", source), e))?;
+        Cow::Owned(decompressed)
+    } else {
+        bytes
+    };
+    Ok(charset::decode(bytes, source))
+}
+
+/// Parse dictionary HTML already fetched some other way, e.g. the body of a response from
+/// an HTTP client with its own authentication or proxy settings. The bytes are decompressed
+/// first if they're gzipped. `parse` reads files the same way.
+///
+/// HTML that isn't UTF-8, such as older editions saved as Latin-1, is transcoded from the
+/// encoding its byte order mark or `<meta>` charset declares. Without either, invalid bytes
+/// are replaced with U+FFFD and a warning is logged.
+#[cfg(feature = "fs")]
+pub fn parse_bytes(bytes: &[u8]) -> Result<Dictionary> {
+    parse_str(&decode_html(bytes, false, "dictionary HTML")?)
+}
+
+/// Parse the given HTML file into a `Vec` of `Entry`. IO or parsing errors may occur. Gzipped
+/// files, such as the `.html.gz` dumps Project Gutenberg distributes, are decompressed first.
+#[cfg(feature = "fs")]
+pub fn parse<P>(file_path: &P) -> Result<Dictionary>
+where
+    P: AsRef<Path>,
+{
+    parse_str(&read_html(file_path.as_ref())?)
+}
+
+/// Like `parse_debug_word_str`, for the HTML file at `file_path`, which may be gzipped.
+#[cfg(feature = "fs")]
+pub fn parse_debug_word<P>(file_path: &P, word: &str) -> Result<WordDiagnostic>
+where
+    P: AsRef<Path>,
+{
+    Ok(parse_debug_word_str(&read_html(file_path.as_ref())?, word))
+}
+
+/// Like `parse`, along with counts of the paragraphs converted and skipped.
+#[cfg(feature = "fs")]
+pub fn parse_with_report<P>(file_path: &P) -> Result<(Dictionary, ParseReport)>
+where
+    P: AsRef<Path>,
+{
+    let entries = parse_entries(file_path);
+    let total_paragraphs = entries.paragraphs.len();
+    let (entries, report) = collect_with_report(entries, total_paragraphs)?;
+    Ok((entries.try_into()?, report))
+}
+
+/// Like `parse`, with control over how entries are extracted.
+#[cfg(feature = "fs")]
+pub fn parse_with_options<P>(file_path: &P, options: &ParseOptions) -> Result<Dictionary>
+where
+    P: AsRef<Path>,
+{
+    collect_entries(parse_entries(file_path).options(options.clone()))?.try_into()
+}
+
+/// Parse the given HTML file, choosing entries and their headwords with CSS selectors rather
+/// than the layout of the Gutenberg dictionary. `parse` is equivalent to selecting `p`
+/// elements whose first child has a `word_` id, with their leading `b` elements as headwords.
+#[cfg(feature = "fs")]
+pub fn parse_with_selector<P>(
+    file_path: &P,
+    entry_selector: &str,
+    word_selector: &str,
+) -> Result<Dictionary>
+where
+    P: AsRef<Path>,
+{
+    parse_with_selector_and_options(
+        file_path,
+        entry_selector,
+        word_selector,
+        &ParseOptions::default(),
+    )
+}
+
+/// Like `parse_with_selector`, with control over how entries are extracted, e.g. to label
+/// them with `ParseOptions::source`.
+#[cfg(feature = "fs")]
+pub fn parse_with_selector_and_options<P>(
+    file_path: &P,
+    entry_selector: &str,
+    word_selector: &str,
+    options: &ParseOptions,
+) -> Result<Dictionary>
+where
+    P: AsRef<Path>,
+{
+    let html = read_html(file_path.as_ref())?;
+    parse_str_with_selector_and_options(&html, entry_selector, word_selector, options)
+}
+
+/// Like `parse_with_selector`, for in-memory HTML.
+pub fn parse_str_with_selector(
+    html: &str,
+    entry_selector: &str,
+    word_selector: &str,
+) -> Result<Dictionary> {
+    parse_str_with_selector_and_options(
+        html,
+        entry_selector,
+        word_selector,
+        &ParseOptions::default(),
+    )
+}
+
+/// Like `parse_str_with_selector`, with control over how entries are extracted.
+pub fn parse_str_with_selector_and_options(
+    html: &str,
+    entry_selector: &str,
+    word_selector: &str,
+    options: &ParseOptions,
+) -> Result<Dictionary> {
+    let entries =
+        Entries::with_selector(html, entry_selector, word_selector)?.options(options.clone());
+    collect_entries(entries)?.try_into()
+}
+
+#[cfg(feature = "fetch")]
+pub async fn parse_url<T: IntoUrl>(url: T) -> Result<Dictionary> {
+    parse_url_with_opts(url, &FetchOptions::default()).await
+}
+
+/// Like `parse_url`, with control over the request timeout and retries.
+#[cfg(feature = "fetch")]
+pub async fn parse_url_with_opts<T: IntoUrl>(url: T, opts: &FetchOptions) -> Result<Dictionary> {
+    entries_from_html(&fetch_html(url, opts, None).await?)?.try_into()
+}
+
+/// Like `parse_url_with_opts`, but the HTML is read from `cache` when it holds an unexpired
+/// copy, and stored there after fetching otherwise.
+#[cfg(feature = "fetch")]
+pub async fn parse_url_cached<T: IntoUrl>(
+    url: T,
+    cache: &UrlCache,
+    opts: &FetchOptions,
+) -> Result<Dictionary> {
+    entries_from_html(&fetch_html(url, opts, Some(cache)).await?)?.try_into()
+}
+
+/// Like `parse_url`, without needing an async runtime.
+#[cfg(feature = "blocking")]
+pub fn parse_url_blocking<T: IntoUrl>(url: T) -> Result<Dictionary> {
+    parse_url_blocking_with_opts(url, &FetchOptions::default())
+}
+
+/// Like `parse_url_blocking`, with control over the request timeout and retries.
+#[cfg(feature = "blocking")]
+pub fn parse_url_blocking_with_opts<T: IntoUrl>(url: T, opts: &FetchOptions) -> Result<Dictionary> {
+    entries_from_html(&fetch::fetch_blocking(url, opts)?)?.try_into()
+}
+
+/// Download the HTML at `url`, going through `cache` if one is given.
+#[cfg(feature = "fetch")]
+pub async fn fetch_html<T: IntoUrl>(
+    url: T,
+    opts: &FetchOptions,
+    cache: Option<&UrlCache>,
+) -> Result<String> {
+    let Some(cache) = cache else {
+        return fetch::fetch(url, opts).await;
+    };
+    let url = url.into_url()?;
+    match cache.get(url.as_str())? {
+        Some(html) => {
+            debug!("Using cached {}", cache.path(url.as_str()).display());
+            Ok(html)
+        }
+        None => {
+            let html = fetch::fetch(url.clone(), opts).await?;
+            cache.put(url.as_str(), &html)?;
+            Ok(html)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        compare_old_english, entries_from_html, entries_from_html_with_progress,
+        entries_from_html_with_report, fold, is_runic, parse_str, parse_str_with_options,
+        parse_str_with_selector, parse_str_with_selector_and_options, split_senses,
+        transliterate_futhorc, Dictionary, DictionaryError, DictionaryOptions, Entries, Entry,
+        Gender, Grammar, Inflection, MatchKind, Page, ParseOptions, Progress, Snippet, SortOrder,
+        WordClass,
+    };
+    #[cfg(feature = "fs")]
+    use super::{
+        parse, parse_bytes, parse_debug_word, parse_debug_word_str, parse_entries,
+        parse_str_with_report, parse_with_report, ParagraphOutcome, MAX_WRITER_HEAP,
+        MIN_WRITER_HEAP,
+    };
+    #[cfg(feature = "fetch")]
+    use super::{parse_url, parse_url_cached, parse_url_with_opts, FetchOptions, UrlCache};
+    #[cfg(feature = "serde")]
+    use super::{ExportHeader, EXPORT_FORMAT_VERSION};
+    #[cfg(feature = "fetch")]
+    use std::io::Read;
+    #[cfg(feature = "fs")]
+    use std::io::Write;
+    #[cfg(feature = "fetch")]
+    use std::time::Duration;
+
+    fn init() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    fn entry(word: &str, definition: &str) -> Entry {
+        Entry {
+            word: word.to_owned(),
+            definition: definition.to_owned(),
+            ..Entry::default()
+        }
+    }
+
+    const FIXTURE: &str = r#"<html><body>
+        <p>Preface text that is not an entry.</p>
+        <p><a id="word_1"></a><b>cyning</b><i>king</i></p>
+        <p><a id="word_2"></a><b>leoht</b><i>light</i></p>
+        <p><a id="word_3"></a><i>malformed</i></p>
+    </body></html>"#;
+
+    #[cfg(feature = "fetch")]
+    #[tokio::test]
+    #[ignore = "downloads the dictionary from gutenberg.org"]
+    async fn test_parse() {
+        init();
+        let dictionary =
+            parse_url("https://www.gutenberg.org/cache/epub/31543/pg31543-images.html".to_string())
+                .await
+                .unwrap();
+
+        let top_docs = dictionary.search("light", None).unwrap();
+        assert_eq!(10, top_docs.len());
+
+        //for (score, doc_address) in top_docs {
+        //    let retrieved_doc = searcher.doc(doc_address).unwrap();
+        //    println!("{score} {:?}", &retrieved_doc);
         //}
     }
+
+    /// Serve each `(status, body)` response over HTTP for one request in turn, returning the URL
+    /// to fetch them from.
+    #[cfg(feature = "fetch")]
+    fn serve(responses: Vec<(&'static str, &'static str)>) -> String {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/dictionary.html", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for (status, body) in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = [0; 1024];
+                let _ = stream.read(&mut request).unwrap();
+                write!(
+                    stream,
+                    "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                )
+                .unwrap();
+            }
+        });
+        url
+    }
+
+    #[cfg(feature = "fetch")]
+    fn serve_once(body: &'static str) -> String {
+        serve(vec![("200 OK", body)])
+    }
+
+    #[cfg(feature = "fetch")]
+    #[tokio::test]
+    async fn test_parse_url_retries() {
+        init();
+        let opts = FetchOptions {
+            retries: 1,
+            backoff: Duration::from_millis(10),
+            ..FetchOptions::default()
+        };
+        let url = serve(vec![("503 Service Unavailable", ""), ("200 OK", FIXTURE)]);
+        let dict = parse_url_with_opts(url.as_str(), &opts).await.unwrap();
+        assert_eq!(1, dict.define("cyning", None).unwrap().len());
+
+        let url = serve(vec![
+            ("503 Service Unavailable", ""),
+            ("503 Service Unavailable", ""),
+        ]);
+        let err = parse_url_with_opts(url.as_str(), &opts)
+            .await
+            .err()
+            .unwrap();
+        assert!(matches!(err, DictionaryError::Fetch(_)), "{err:?}");
+
+        // Client errors aren't worth retrying, so the 200 is never requested
+        let url = serve(vec![("404 Not Found", ""), ("200 OK", FIXTURE)]);
+        assert!(parse_url_with_opts(url.as_str(), &opts).await.is_err());
+    }
+
+    #[cfg(feature = "fetch")]
+    #[tokio::test]
+    async fn test_parse_url_timeout() {
+        init();
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/dictionary.html", listener.local_addr().unwrap());
+        let opts = FetchOptions {
+            timeout: Duration::from_millis(100),
+            retries: 0,
+            ..FetchOptions::default()
+        };
+        // The listener accepts connections but never responds
+        let err = parse_url_with_opts(url.as_str(), &opts)
+            .await
+            .err()
+            .unwrap();
+        match err {
+            DictionaryError::Fetch(e) => assert!(e.is_timeout(), "{e:?}"),
+            e => panic!("expected a fetch error, got {e:?}"),
+        }
+        drop(listener);
+    }
+
+    #[cfg(feature = "fetch")]
+    #[tokio::test]
+    async fn test_parse_url_cached() {
+        init();
+        let dir = tempfile::tempdir().unwrap();
+        let cache = UrlCache::new(dir.path());
+        let opts = FetchOptions {
+            retries: 0,
+            ..FetchOptions::default()
+        };
+        let url = serve_once(FIXTURE);
+
+        let fetched = parse_url_cached(url.as_str(), &cache, &opts).await.unwrap();
+        assert_eq!(1, fetched.define("cyning", None).unwrap().len());
+        assert!(cache.path(&url).exists());
+
+        // The server has gone, so this can only be answered from the cache
+        let cached = parse_url_cached(url.as_str(), &cache, &opts).await.unwrap();
+        assert_eq!(1, cached.define("cyning", None).unwrap().len());
+
+        let expired = cache.clone().ttl(Duration::ZERO);
+        std::thread::sleep(Duration::from_millis(10));
+        assert!(expired.get(&url).unwrap().is_none());
+        assert!(parse_url_cached(url.as_str(), &expired, &opts)
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn test_parse_str() {
+        init();
+        let dictionary = parse_str(FIXTURE).unwrap();
+
+        let results = dictionary.define("cyning", None).unwrap();
+        assert_eq!(1, results.len());
+        assert_eq!("cyning", results[0].word);
+
+        let results = dictionary.search("light", None).unwrap();
+        assert_eq!(1, results.len());
+        assert_eq!("leoht", results[0].word);
+    }
+
+    #[test]
+    fn test_parse_skips_paragraph_without_headword() {
+        init();
+        let dictionary = parse_str(FIXTURE).unwrap();
+
+        assert!(dictionary.search("malformed", None).unwrap().is_empty());
+        assert_eq!(1, dictionary.define("leoht", None).unwrap().len());
+    }
+
+    #[test]
+    fn test_parse_with_selector() {
+        init();
+        let divs = r#"<html><body>
+            <div class="entry" id="e1"><span class="hw">cyning</span>, m. <em>king</em></div>
+            <div class="entry"><span class="hw">leoht</span> light</div>
+            <div class="entry">no headword</div>
+            <div class="other"><span class="hw">stan</span> stone</div>
+            </body></html>"#;
+        let dictionary = parse_str_with_selector(divs, "div.entry", ".hw").unwrap();
+        let cyning = dictionary.define("cyning", None).unwrap();
+        assert_eq!("king", cyning[0].definition);
+        assert_eq!(Some("m."), cyning[0].part_of_speech.as_deref());
+        assert_eq!(Some("e1"), cyning[0].id.as_deref());
+        assert_eq!(
+            "light",
+            dictionary.define("leoht", None).unwrap()[0].definition
+        );
+        assert!(dictionary.define("stan", None).unwrap().is_empty());
+
+        let pairs = r#"<html><body><dl>
+            <dt>cyning</dt><dd>king, ruler</dd>
+            <dt>leoht</dt><dd>light</dd>
+            </dl></body></html>"#;
+        let dictionary = parse_str_with_selector(pairs, "dt", "dt").unwrap();
+        assert_eq!(
+            "king, ruler",
+            dictionary.define("cyning", None).unwrap()[0].definition
+        );
+        assert_eq!(
+            "light",
+            dictionary.define("leoht", None).unwrap()[0].definition
+        );
+
+        assert!(matches!(
+            parse_str_with_selector(pairs, "dt[", "dt").err().unwrap(),
+            DictionaryError::InvalidSelector(_)
+        ));
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_parse_entries() {
+        init();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("dictionary.html");
+        std::fs::write(&path, FIXTURE).unwrap();
+
+        let entries: Vec<_> = parse_entries(&path).collect();
+        assert_eq!(3, entries.len());
+        assert_eq!("cyning", entries[0].as_ref().unwrap().word);
+        assert_eq!("leoht", entries[1].as_ref().unwrap().word);
+        assert!(matches!(entries[2], Err(DictionaryError::Parse(_))));
+        assert_eq!(
+            1,
+            parse(&path).unwrap().define("leoht", None).unwrap().len()
+        );
+
+        let missing: Vec<_> = parse_entries(&dir.path().join("missing.html")).collect();
+        assert!(matches!(missing[..], [Err(DictionaryError::Io { .. })]));
+        assert!(parse(&dir.path().join("missing.html")).is_err());
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_parse_gzip() {
+        use std::io::Write;
+
+        init();
+        let dir = tempfile::tempdir().unwrap();
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(FIXTURE.as_bytes()).unwrap();
+        let gzipped = encoder.finish().unwrap();
+
+        for name in ["dictionary.html.gz", "dictionary.html"] {
+            let path = dir.path().join(name);
+            std::fs::write(&path, &gzipped).unwrap();
+            let dictionary = parse(&path).unwrap();
+            assert_eq!(
+                1,
+                dictionary.define("leoht", None).unwrap().len(),
+                "{}",
+                name
+            );
+        }
+
+        let path = dir.path().join("corrupt.html.gz");
+        std::fs::write(&path, FIXTURE).unwrap();
+        assert!(matches!(parse(&path), Err(DictionaryError::Io { .. })));
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_parse_bytes() {
+        use std::io::Write;
+
+        init();
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(FIXTURE.as_bytes()).unwrap();
+        let gzipped = encoder.finish().unwrap();
+        for bytes in [FIXTURE.as_bytes(), &gzipped] {
+            let dictionary = parse_bytes(bytes).unwrap();
+            assert_eq!(
+                "king",
+                dictionary.define("cyning", None).unwrap()[0].definition
+            );
+            assert_eq!(2, dictionary.len());
+        }
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_parse_latin1() {
+        init();
+        let paragraph = b"<p><a id=\"word_1\"></a><b>\xe6sc</b> ash, \xfe\xe6t \xf0e</p>";
+        for meta in [
+            &b"<meta charset=\"ISO-8859-1\">"[..],
+            b"<meta http-equiv=\"Content-Type\" content=\"text/html; charset=iso-8859-1\" />",
+            b"<META CHARSET=latin1>",
+        ] {
+            let html = [b"<html><head>", meta, b"</head><body>", paragraph].concat();
+            let dictionary = parse_bytes(&html).unwrap();
+            let entry = &dictionary.define("æsc", None).unwrap()[0];
+            assert_eq!(
+                "ash, þæt ðe",
+                entry.definition,
+                "{}",
+                String::from_utf8_lossy(meta)
+            );
+        }
+
+        // Without a declaration, invalid bytes are replaced rather than failing the parse
+        let dictionary = parse_bytes(paragraph).unwrap();
+        let entry = &dictionary.define("\u{fffd}sc", None).unwrap()[0];
+        assert_eq!("ash, \u{fffd}\u{fffd}t \u{fffd}e", entry.definition);
+
+        // UTF-8 is kept as it is, whatever it declares
+        let html = "<meta charset=\"iso-8859-1\"><p><a id=\"word_1\"></a><b>æsc</b> ash</p>";
+        let dictionary = parse_bytes(html.as_bytes()).unwrap();
+        assert_eq!(1, dictionary.define("æsc", None).unwrap().len());
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_parse_debug_word() {
+        init();
+        let html = r#"<html><body>
+            <p>An editor's note on god.</p>
+            <p><a id="word_1"></a><b>god</b> m. God, a god</p>
+            <p>which the god of the heathen was called</p>
+            <p><a id="word_2"></a><b>gōd</b> adj. good</p>
+            <p><a id="word_3"></a>god without a bold headword</p>
+            <p><a id="word_4"></a><b>godspell</b> gospel, from god and spell</p>
+            <p><a id="word_5"></a><b>stan</b> a stone</p>
+            </body></html>"#;
+        let diagnostic = parse_debug_word_str(html, "god");
+        assert_eq!(
+            vec!["god"],
+            diagnostic.entries().map(|e| &e.word).collect::<Vec<_>>()
+        );
+        let outcomes: Vec<_> = diagnostic
+            .paragraphs
+            .iter()
+            .map(|p| (p.id.as_deref(), &p.outcome))
+            .collect();
+        assert_eq!(6, outcomes.len(), "{outcomes:?}");
+        assert!(
+            matches!(outcomes[1], (Some("word_1"), ParagraphOutcome::Converted(e)) if e[0].definition == "God, a god which the god of the heathen was called")
+        );
+        assert_eq!((None, &ParagraphOutcome::Continuation), outcomes[2]);
+        assert!(matches!(
+            outcomes[3],
+            (Some("word_2"), ParagraphOutcome::Converted(_))
+        ));
+        assert_eq!(
+            (
+                Some("word_3"),
+                &ParagraphOutcome::Skipped("Paragraph has no bold headword".to_owned())
+            ),
+            outcomes[4]
+        );
+        assert_eq!((None, &ParagraphOutcome::NotAnEntry), outcomes[0]);
+        assert!(matches!(
+            outcomes[5],
+            (Some("word_4"), ParagraphOutcome::Converted(_))
+        ));
+
+        let report = diagnostic.to_string();
+        assert!(report.starts_with("\"god\": defined in 1 of the 6 paragraphs mentioning it\n"));
+        assert!(report.contains("Paragraph word_3: skipped: Paragraph has no bold headword\n"));
+        assert!(
+            report.contains("  god (m.) — God, a god which the god of the heathen was called\n")
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("dictionary.html");
+        std::fs::write(&path, html).unwrap();
+        assert_eq!(diagnostic, parse_debug_word(&path, "god").unwrap());
+        assert!(parse_debug_word(&path, "cyning")
+            .unwrap()
+            .paragraphs
+            .is_empty());
+    }
+
+    #[test]
+    fn test_parse_homographs() {
+        init();
+        let dictionary = parse_str(
+            r#"<p><a id="word_1"></a><b>cyning</b>, <b>cyninge</b> <i>king, ruler</i></p>"#,
+        )
+        .unwrap();
+
+        let cyning = dictionary.define("cyning", None).unwrap();
+        assert_eq!(1, cyning.len());
+        assert_eq!("cyning", cyning[0].word);
+        let cyninge = dictionary.define("cyninge", None).unwrap();
+        assert_eq!(1, cyninge.len());
+        assert_eq!("cyninge", cyninge[0].word);
+        assert_eq!("king, ruler", cyning[0].definition);
+        assert_eq!(cyning[0].definition, cyninge[0].definition);
+        assert_eq!(2, dictionary.search("ruler", None).unwrap().len());
+    }
+
+    #[test]
+    fn test_parse_part_of_speech() {
+        init();
+        let dictionary = parse_str(
+            r#"<p><a id="word_1"></a><b>cyning</b> m. king</p>
+            <p><a id="word_2"></a><b>god</b> adj. good</p>
+            <p><a id="word_3"></a><b>mann</b> man, person</p>"#,
+        )
+        .unwrap();
+
+        let cyning = &dictionary.define("cyning", None).unwrap()[0];
+        assert_eq!(Some("m.".to_owned()), cyning.part_of_speech);
+        assert_eq!("king", cyning.definition);
+        let god = &dictionary.define("god", None).unwrap()[0];
+        assert_eq!(Some("adj.".to_owned()), god.part_of_speech);
+        assert_eq!("good", god.definition);
+        let mann = &dictionary.define("mann", None).unwrap()[0];
+        assert_eq!(None, mann.part_of_speech);
+        assert_eq!("man, person", mann.definition);
+    }
+
+    #[test]
+    fn test_headword_not_in_definition() {
+        init();
+        let dictionary = parse_str(
+            r#"<p><a id="word_1" name="word_1">cyning</a><b>cyning</b> m. king</p>
+            <p><a id="word_2"><b>cwen</b></a>, <b>cwene</b> f. queen</p>
+            <p><a id="word_3"></a><b>stan</b> stan, a stone, <i>a rock <b>or</b> cliff</i></p>
+            <p><a id="word_4"></a><b>hlaford</b>, sm. a lord</p>"#,
+        )
+        .unwrap();
+        let cyning = &dictionary.define("cyning", None).unwrap()[0];
+        assert_eq!("king", cyning.definition);
+        assert_eq!(Some("m."), cyning.part_of_speech.as_deref());
+        assert_eq!(Some("word_1"), cyning.id.as_deref());
+        for word in ["cwen", "cwene"] {
+            let entry = &dictionary.define(word, None).unwrap()[0];
+            assert_eq!("queen", entry.definition);
+            assert_eq!(Some("word_2"), entry.id.as_deref());
+        }
+        // Elements within the definition contribute all their text, not just the first node
+        let stan = &dictionary.define("stan", None).unwrap()[0];
+        assert_eq!("a stone, a rock or cliff", stan.definition);
+        // A comma between the headword and the part of speech isn't part of either
+        let hlaford = &dictionary.define("hlaford", None).unwrap()[0];
+        assert_eq!("a lord", hlaford.definition);
+        assert_eq!(Some("sm."), hlaford.part_of_speech.as_deref());
+        assert!(hlaford.grammar.is_some());
+        for entry in dictionary.iter_entries() {
+            let entry = entry.unwrap();
+            assert!(!entry.definition.starts_with(&entry.word), "{entry:?}");
+        }
+    }
+
+    #[test]
+    fn test_grammar() {
+        init();
+        assert_eq!(
+            Some(Grammar {
+                class: WordClass::Noun,
+                gender: Some(Gender::Feminine),
+                inflection: Some(Inflection::Weak),
+            }),
+            Grammar::from_abbreviation("wf.")
+        );
+        assert_eq!(
+            Some(Grammar {
+                class: WordClass::Verb,
+                gender: None,
+                inflection: Some(Inflection::Anomalous),
+            }),
+            Grammar::from_abbreviation("anv.")
+        );
+        assert_eq!(None, Grammar::from_abbreviation("pl."));
+        assert_eq!(None, Grammar::from_abbreviation("indecl."));
+
+        let dictionary = parse_str(
+            r#"<p><a id="word_1"></a><b>cyning</b> sm. king</p>
+            <p><a id="word_2"></a><b>cwen</b> sf. queen</p>
+            <p><a id="word_3"></a><b>bindan</b> sv. to bind</p>
+            <p><a id="word_4"></a><b>mann</b> man, person</p>"#,
+        )
+        .unwrap();
+        let cyning = &dictionary.define("cyning", None).unwrap()[0];
+        assert_eq!(Grammar::from_abbreviation("sm."), cyning.grammar);
+        assert_eq!("king", cyning.definition);
+        assert_eq!(None, dictionary.define("mann", None).unwrap()[0].grammar);
+
+        let words = |query| -> Vec<String> {
+            let mut words: Vec<_> = dictionary
+                .search(query, None)
+                .unwrap()
+                .into_iter()
+                .map(|e| e.word)
+                .collect();
+            words.sort();
+            words
+        };
+        assert_eq!(vec!["cwen"], words("gender:feminine"));
+        assert_eq!(vec!["cwen", "cyning"], words("word_class:noun"));
+        assert_eq!(vec!["bindan", "cwen", "cyning"], words("inflection:strong"));
+        assert_eq!(vec!["bindan"], words("bind AND word_class:verb"));
+    }
+
+    #[test]
+    fn test_strip_artifacts() {
+        init();
+        let html = r##"<html><body>
+            <p><a id="word_1"></a><b>stan</b> a stone<span class="pagenum"><a id="Page_12">[Pg 12]</a></span>, <a class="pageno">13</a>rock<sup>1</sup> <a class="fnanchor" href="#Footnote_1">[1]</a></p>
+            </body></html>"##;
+        let entries = entries_from_html(html).unwrap();
+        assert_eq!(1, entries.len());
+        let definition = &entries[0].definition;
+        assert!(!definition.contains(|c: char| c.is_ascii_digit() || c == '['));
+        let words: Vec<&str> = definition.split_whitespace().collect();
+        assert_eq!(vec!["a", "stone", ",", "rock"], words);
+    }
+
+    #[test]
+    fn test_continuation_paragraphs() {
+        init();
+        let html = r#"<html><body>
+            <p><a id="word_1"></a><b>cyning</b> m. a king, ruler;</p>
+            <p>of the West Saxons<span class="pagenum">[Pg 40]</span>, Ælfred.</p>
+            <!-- page break -->
+            <p>See cyne-.</p>
+            <p><a id="word_2"></a><b>leoht</b> n. light</p>
+            <h2>M</h2>
+            <p>A heading's note, not part of leoht.</p>
+            </body></html>"#;
+        let entries = entries_from_html(html).unwrap();
+        assert_eq!(2, entries.len());
+        assert_eq!(
+            "a king, ruler; of the West Saxons, Ælfred. See cyne-.",
+            entries[0].definition
+        );
+        assert_eq!(vec!["cyne-"], entries[0].see_also);
+        assert_eq!("light", entries[1].definition);
+    }
+
+    #[test]
+    fn test_collapse_whitespace() {
+        init();
+        let html = "<html><body>
+            <p><a id=\"word_1\"></a><b>cyning</b> a <i>king</i>  of\n   the <i>West</i>\tSaxons</p>
+            </body></html>";
+        let entries = entries_from_html(html).unwrap();
+        assert_eq!("a king of the West Saxons", entries[0].definition);
+    }
+
+    #[test]
+    fn test_markdown_emphasis() {
+        init();
+        let html = r#"<html><body>
+            <p><a id="word_1"></a><b>cyning</b> a <i>king</i>, <i>rex</i>; <b>see</b> cyne-</p>
+            </body></html>"#;
+        let plain = parse_str(html).unwrap();
+        let plain = &plain.define("cyning", None).unwrap()[0];
+        assert!(!plain.definition.contains('*'), "{}", plain.definition);
+
+        let options = ParseOptions {
+            markdown: true,
+            ..ParseOptions::default()
+        };
+        let markdown = parse_str_with_options(html, &options).unwrap();
+        let markdown = &markdown.define("cyning", None).unwrap()[0];
+        assert_eq!("cyning", markdown.word);
+        let words: Vec<&str> = markdown.definition.split_whitespace().collect();
+        assert_eq!(
+            vec!["a", "*king*", ",", "*rex*", ";", "**see**", "cyne-"],
+            words
+        );
+    }
+
+    #[test]
+    fn test_see_also() {
+        init();
+        let html = r#"<html><body>
+            <p><a id="word_1"></a><b>cyng</b> See <i>cyning</i>.</p>
+            <p><a id="word_2"></a><b>cynedom</b> a kingdom; cf. cyne-rice, cynn and cyning</p>
+            <p><a id="word_3"></a><b>seon</b> to see, behold. See the light of day.</p>
+            </body></html>"#;
+        let dictionary = parse_str(html).unwrap();
+        let refs = |word: &str| dictionary.define(word, None).unwrap()[0].see_also.clone();
+        assert_eq!(vec!["cyning"], refs("cyng"));
+        assert_eq!(vec!["cyne-rice", "cynn", "cyning"], refs("cynedom"));
+        assert!(refs("seon").is_empty());
+    }
+
+    #[test]
+    fn test_normalize_wynn() {
+        init();
+        let html = r#"<html><body>
+            <p><a id="word_1"></a><b>ƿord</b> a word; cf. ƿyrd</p>
+            <p><a id="word_2"></a><b>Ƿessex</b> Wessex</p>
+            </body></html>"#;
+        let dictionary = parse_str(html).unwrap();
+        let word = &dictionary.define("word", None).unwrap()[0];
+        assert_eq!(Some("ƿord"), word.original_word.as_deref());
+        assert_eq!(vec!["wyrd"], word.see_also);
+        assert_eq!("word", dictionary.search("word", None).unwrap()[0].word);
+        assert_eq!("Wessex", dictionary.define("Wessex", None).unwrap()[0].word);
+
+        let options = ParseOptions {
+            normalize_wynn: false,
+            ..ParseOptions::default()
+        };
+        let dictionary = parse_str_with_options(html, &options).unwrap();
+        let word = &dictionary.define("ƿord", None).unwrap()[0];
+        assert_eq!(None, word.original_word);
+        assert!(dictionary.define("word", None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_entry_display() {
+        let mut entry = Entry {
+            word: "cyning".to_owned(),
+            definition: "king, ruler".to_owned(),
+            part_of_speech: Some("m.".to_owned()),
+            grammar: None,
+            id: Some("word_1".to_owned()),
+            see_also: Vec::new(),
+            original_word: None,
+            ordinal: None,
+            senses: Vec::new(),
+            source: None,
+        };
+        assert_eq!("cyning (m.) — king, ruler", entry.to_string());
+        entry.part_of_speech = None;
+        assert_eq!("cyning — king, ruler", entry.to_string());
+    }
+
+    #[test]
+    fn test_entry_id() {
+        init();
+        let dictionary = parse_str(FIXTURE).unwrap();
+        let cyning = &dictionary.define("cyning", None).unwrap()[0];
+        assert_eq!(Some("word_1".to_owned()), cyning.id);
+
+        let dictionary = Dictionary::new(vec![Entry {
+            word: "cyning".to_owned(),
+            definition: "king".to_owned(),
+            ..Default::default()
+        }])
+        .unwrap();
+        assert_eq!(None, dictionary.define("cyning", None).unwrap()[0].id);
+    }
+
+    #[test]
+    fn test_decode_entities() {
+        init();
+        let dictionary = parse_str(
+            r#"<p><a id="word_1"></a><b>&amp;aelig;sc</b> m. an ash-tree</p>
+            <p><a id="word_2"></a><b>&amp;eth;&amp;#275;od</b> f. a people, &amp;thorn;eod</p>
+            <p><a id="word_3"></a><b>&aelig;&thorn;ele</b> adj. noble &amp;amp; excellent</p>"#,
+        )
+        .unwrap();
+
+        let aesc = dictionary.search("æsc", None).unwrap();
+        assert_eq!(1, aesc.len());
+        assert_eq!("æsc", aesc[0].word);
+        let theod = &dictionary.define("ðēod", None).unwrap()[0];
+        assert_eq!("a people, þeod", theod.definition);
+        let aethele = &dictionary.define("æþele", None).unwrap()[0];
+        assert_eq!("noble & excellent", aethele.definition);
+    }
+
+    #[test]
+    fn test_fold() {
+        assert_eq!("god", fold("gōd"));
+        assert_eq!("aesc", fold("æsc"));
+        assert_eq!("Aelfred", fold("Ælfred"));
+        assert_eq!("theod", fold("ðēod"));
+        assert_eq!("thaer", fold("þǣr"));
+    }
+
+    #[test]
+    fn test_folded_search() {
+        init();
+        let dictionary = parse_str(
+            r#"<p><a id="word_1"></a><b>god</b> m. God, a god</p>
+            <p><a id="word_2"></a><b>gōd</b> adj. good</p>
+            <p><a id="word_3"></a><b>&aelig;sc</b> m. an ash-tree</p>"#,
+        )
+        .unwrap();
+
+        assert_eq!(1, dictionary.define("god", None).unwrap().len());
+        let mut words: Vec<_> = dictionary
+            .define_folded("god", None)
+            .unwrap()
+            .into_iter()
+            .map(|e| e.word)
+            .collect();
+        words.sort();
+        assert_eq!(vec!["god", "gōd"], words);
+        assert_eq!(
+            "æsc",
+            dictionary.define_folded("aesc", None).unwrap()[0].word
+        );
+        assert_eq!(2, dictionary.search_folded("gōd", None).unwrap().len());
+    }
+
+    #[test]
+    fn test_search_scored() {
+        init();
+        let dictionary = parse_str(
+            r#"<p><a id="word_1"></a><b>leoht</b> n. light, light of day</p>
+            <p><a id="word_2"></a><b>dæg</b> m. day, the time of light</p>"#,
+        )
+        .unwrap();
+
+        let scored = dictionary.search_scored("light", None).unwrap();
+        assert_eq!(2, scored.len());
+        assert!(scored.iter().all(|(score, _)| *score > 0.0));
+        assert!(scored[0].0 >= scored[1].0);
+        let entries: Vec<_> = scored.into_iter().map(|(_, e)| e).collect();
+        assert_eq!(entries, dictionary.search("light", None).unwrap());
+    }
+
+    #[test]
+    fn test_define_exact() {
+        init();
+        let dictionary = parse_str(
+            r#"<p><a id="word_1"></a><b>god</b> m. God, a god</p>
+            <p><a id="word_2"></a><b>gōd</b> adj. good</p>
+            <p><a id="word_3"></a><b>god-spell</b> n. gospel</p>
+            <p><a id="word_4"></a><b>heofon</b> m. heaven, the home of god</p>"#,
+        )
+        .unwrap();
+
+        let god = dictionary.define("god", None).unwrap();
+        assert_eq!(1, god.len());
+        assert_eq!("God, a god", god[0].definition);
+        assert_eq!(
+            "good",
+            dictionary.define("gōd", None).unwrap()[0].definition
+        );
+        assert!(dictionary.define("spell", None).unwrap().is_empty());
+        assert_eq!(2, dictionary.define_loose("god", None).unwrap().len());
+    }
+
+    #[test]
+    fn test_define_limit() {
+        init();
+        let html: String = (0..15)
+            .map(|i| {
+                format!(
+                    r#"<p><a id="word_{}"></a><b>wyrd</b> f. fate, sense {}</p>"#,
+                    i, i
+                )
+            })
+            .collect();
+        let dictionary = parse_str(&html).unwrap();
+
+        assert_eq!(10, dictionary.define("wyrd", None).unwrap().len());
+        assert_eq!(15, dictionary.define("wyrd", Some(20)).unwrap().len());
+        assert_eq!(3, dictionary.define("wyrd", Some(3)).unwrap().len());
+    }
+
+    #[test]
+    fn test_pagination() {
+        init();
+        let html: String = (0..25)
+            .map(|i| {
+                format!(
+                    r#"<p><a id="word_{}"></a><b>wyrd</b> f. fate, sense {}</p>"#,
+                    i, i
+                )
+            })
+            .collect();
+        let dictionary = parse_str(&html).unwrap();
+
+        let first = dictionary.search_page("fate", Page::default()).unwrap();
+        assert_eq!(25, first.total);
+        assert_eq!(10, first.entries.len());
+        let last = dictionary
+            .search_page(
+                "fate",
+                Page {
+                    offset: 20,
+                    limit: 10,
+                },
+            )
+            .unwrap();
+        assert_eq!(25, last.total);
+        assert_eq!(5, last.entries.len());
+        assert!(first.entries.iter().all(|e| !last.entries.contains(e)));
+        let beyond = dictionary
+            .define_page(
+                "wyrd",
+                Page {
+                    offset: 30,
+                    limit: 10,
+                },
+            )
+            .unwrap();
+        assert_eq!(25, beyond.total);
+        assert!(beyond.entries.is_empty());
+        let empty = dictionary
+            .define_page(
+                "wyrd",
+                Page {
+                    offset: 0,
+                    limit: 0,
+                },
+            )
+            .unwrap();
+        assert_eq!(25, empty.total);
+        assert!(empty.entries.is_empty());
+    }
+
+    #[test]
+    fn test_suggest() {
+        init();
+        let mut dictionary = Dictionary::new(vec![
+            entry("cyning", "a word"),
+            entry("cynedom", "a word"),
+            entry("cwen", "a word"),
+        ])
+        .unwrap();
+        // A second segment, with a duplicate headword
+        dictionary
+            .extend(vec![
+                entry("cynn", "a word"),
+                entry("cyning", "a word"),
+                entry("stan", "a word"),
+            ])
+            .unwrap();
+        dictionary.commit().unwrap();
+
+        assert_eq!(
+            vec!["cynedom", "cyning", "cynn"],
+            dictionary.suggest("cyn", 10).unwrap()
+        );
+        assert_eq!(
+            vec!["cynedom", "cyning"],
+            dictionary.suggest("cyn", 2).unwrap()
+        );
+        assert_eq!(vec!["cwen"], dictionary.suggest("cw", 10).unwrap());
+        assert!(dictionary.suggest("x", 10).unwrap().is_empty());
+        assert!(dictionary.suggest("cyn", 0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_did_you_mean() {
+        init();
+        let dictionary = Dictionary::new(vec![
+            entry("cyning", "a word"),
+            entry("cyning", "a word"),
+            entry("cyng", "a word"),
+            entry("stan", "a word"),
+        ])
+        .unwrap();
+        assert_eq!(
+            vec!["cyning"],
+            dictionary.did_you_mean("cyninc", 3).unwrap()
+        );
+        assert_eq!(vec!["stan"], dictionary.did_you_mean("sten", 3).unwrap());
+        assert_eq!(vec!["stan"], dictionary.did_you_mean("stxx", 3).unwrap());
+        assert!(dictionary.did_you_mean("leoht", 3).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_compare_old_english() {
+        let mut words = vec![
+            "þegn", "af", "æsc", "ad", "Ūþ", "tungol", "ðā", "cyning", "ƿord", "ūt", "a-bannan",
+            "ac", "wer", "Abbod",
+        ];
+        words.sort_by(|a, b| compare_old_english(a, b));
+        assert_eq!(
+            vec![
+                "a-bannan", "Abbod", "ac", "ad", "æsc", "af", "cyning", "tungol", "ðā", "þegn",
+                "ūt", "Ūþ", "wer", "ƿord"
+            ],
+            words
+        );
+    }
+
+    #[test]
+    fn test_search_sorted() {
+        init();
+        let dictionary = Dictionary::new(vec![
+            entry("þeoden", "a king, lord"),
+            entry("æþeling", "a prince"),
+            entry("cyning", "a king, king of kings"),
+        ])
+        .unwrap();
+        let words = |sort| -> Vec<String> {
+            dictionary
+                .search_sorted("king OR prince", None, sort)
+                .unwrap()
+                .into_iter()
+                .map(|e| e.word)
+                .collect()
+        };
+        assert_eq!(
+            vec!["æþeling", "cyning", "þeoden"],
+            words(SortOrder::AlphabeticalOE)
+        );
+        assert_eq!(
+            dictionary
+                .search("king OR prince", None)
+                .unwrap()
+                .into_iter()
+                .map(|e| e.word)
+                .collect::<Vec<_>>(),
+            words(SortOrder::Relevance)
+        );
+    }
+
+    #[test]
+    fn test_random() {
+        init();
+        let dictionary = parse_str(FIXTURE).unwrap();
+        let entry = dictionary.random().unwrap();
+        assert!(dictionary.iter_entries().any(|e| e.unwrap() == entry));
+        assert_eq!(
+            dictionary.random_with_seed(7).unwrap(),
+            dictionary.random_with_seed(7).unwrap()
+        );
+        let seen: std::collections::HashSet<String> = (0..50)
+            .map(|seed| dictionary.random_with_seed(seed).unwrap().word)
+            .collect();
+        assert!(seen.len() > 1);
+
+        let empty = Dictionary::new(Vec::new()).unwrap();
+        assert!(matches!(empty.random(), Err(DictionaryError::Empty)));
+    }
+
+    #[test]
+    fn test_words_starting_with() {
+        init();
+        let entries = (0..15).map(|i| entry(&format!("cyn{}", i), "a word"));
+        let dictionary = Dictionary::new(
+            [
+                "cyning", "Cynewulf", "cwen", "æsc", "ac", "ðegn", "þeod", "a.b", "axb",
+            ]
+            .into_iter()
+            .map(|word| entry(word, "a word"))
+            .chain(entries)
+            .collect(),
+        )
+        .unwrap();
+        let words = |prefix: &str| -> Vec<String> {
+            dictionary
+                .words_starting_with(prefix)
+                .unwrap()
+                .into_iter()
+                .map(|e| e.word)
+                .collect()
+        };
+        // Every match is returned, not just the top 10
+        assert_eq!(17, words("cyn").len());
+        assert_eq!(vec!["Cynewulf"], words("cyne"));
+        assert_eq!(vec!["Cynewulf"], words("CYNEW"));
+        assert_eq!(vec!["æsc"], words("æ"));
+        assert_eq!(vec!["a.b", "ac", "axb"], words("a"));
+        assert_eq!(vec!["a.b"], words("a."));
+        assert_eq!(vec!["ðegn", "þeod"], words("þ"));
+        assert_eq!(vec!["ðegn"], words("þeg"));
+        assert!(words("x").is_empty());
+    }
+
+    #[test]
+    fn test_words_containing() {
+        init();
+        let dictionary = Dictionary::new(
+            [
+                "cyning",
+                "Cynewulf",
+                "æþeling",
+                "wulf",
+                "ðegn",
+                "a.b",
+                "axb",
+            ]
+            .into_iter()
+            .map(|word| entry(word, "a word"))
+            .collect(),
+        )
+        .unwrap();
+        let words = |substring: &str| -> Vec<String> {
+            dictionary
+                .words_containing(substring)
+                .unwrap()
+                .into_iter()
+                .map(|e| e.word)
+                .collect()
+        };
+        assert_eq!(vec!["Cynewulf", "wulf"], words("WULF"));
+        assert_eq!(vec!["æþeling", "cyning"], words("ing"));
+        // þ and ð match each other wherever they fall
+        assert_eq!(vec!["æþeling"], words("ðel"));
+        // Regex syntax is matched literally
+        assert_eq!(vec!["a.b"], words("."));
+        assert!(words("xyz").is_empty());
+        // Short substrings still return every match
+        assert_eq!(7, words("").len());
+    }
+
+    #[test]
+    fn test_count() {
+        init();
+        let entries = (0..25)
+            .map(|i| Entry {
+                word: format!("word{}", i),
+                definition: "a king".to_owned(),
+                ..Entry::default()
+            })
+            .collect();
+        let dictionary = Dictionary::new(entries).unwrap();
+        assert_eq!(25, dictionary.count("king").unwrap());
+        assert_eq!(1, dictionary.count("word3").unwrap());
+        assert_eq!(0, dictionary.count("queen").unwrap());
+        assert!(dictionary.count("definition:\"king").is_err());
+    }
+
+    #[test]
+    fn test_search_fuzzy() {
+        init();
+        let dictionary = parse_str(FIXTURE).unwrap();
+
+        assert!(dictionary.define("cyninge", None).unwrap().is_empty());
+        let results = dictionary.search_fuzzy("cyninge", 1, None).unwrap();
+        assert_eq!(1, results.len());
+        assert_eq!("cyning", results[0].word);
+        assert!(dictionary.search_fuzzy("cyng", 1, None).unwrap().is_empty());
+        assert_eq!(1, dictionary.search_fuzzy("Cyng", 2, None).unwrap().len());
+        assert!(dictionary.search_fuzzy("cyning", 3, None).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_entry_serde() {
+        let entry = Entry {
+            word: "cyning".to_owned(),
+            definition: "king".to_owned(),
+            part_of_speech: Some("m.".to_owned()),
+            grammar: Grammar::from_abbreviation("m."),
+            id: Some("word_1".to_owned()),
+            see_also: Vec::new(),
+            original_word: None,
+            ordinal: None,
+            senses: Vec::new(),
+            source: None,
+        };
+        let json = serde_json::to_value(&entry).unwrap();
+        assert_eq!(
+            serde_json::json!({
+                "word": "cyning",
+                "definition": "king",
+                "part_of_speech": "m.",
+                "grammar": {"class": "noun", "gender": "masculine"},
+                "id": "word_1",
+            }),
+            json
+        );
+        assert_eq!(entry, serde_json::from_value(json).unwrap());
+
+        let partial: Entry =
+            serde_json::from_str(r#"{"word": "stan", "definition": "stone"}"#).unwrap();
+        assert_eq!(None, partial.part_of_speech);
+        assert_eq!(None, partial.id);
+    }
+
+    #[test]
+    fn test_query_errors() {
+        init();
+        let dictionary = parse_str(FIXTURE).unwrap();
+
+        assert!(matches!(
+            dictionary.search("nonexistent:king", None),
+            Err(DictionaryError::QueryParse { .. })
+        ));
+        assert!(matches!(
+            dictionary.search_fuzzy("cyning", 5, None),
+            Err(DictionaryError::InvalidQuery(_))
+        ));
+        let message = |query| dictionary.search(query, None).err().unwrap().to_string();
+        assert_eq!(
+            "Invalid query: unknown field 'defnition', did you mean 'definition'?",
+            message("defnition:king")
+        );
+        assert!(message("nonexistent:king")
+            .starts_with("Invalid query: unknown field 'nonexistent', expected one of word, "));
+        assert_eq!(
+            r#"Invalid query: missing delimiter " at position 16"#,
+            message(r#"definition:"king"#)
+        );
+        assert_eq!(
+            "Invalid query: a query can't only exclude terms; add a term to search for",
+            message("-king")
+        );
+        assert_eq!(
+            "Invalid query: unparsed end of query at position 4",
+            message("king)")
+        );
+    }
+
+    #[test]
+    fn test_pathological_queries() {
+        init();
+        let dictionary = parse_str(FIXTURE).unwrap();
+        let nested = format!("{}king{}", "(".repeat(100_000), ")".repeat(100_000));
+        let unclosed = "(".repeat(100_000);
+        let long = format!("{}king", "-(".repeat(50_000));
+        for query in [
+            nested.as_str(),
+            &unclosed,
+            &long,
+            "king^abc",
+            "OR OR",
+            "ordinal:one",
+        ] {
+            assert!(dictionary.search(query, None).is_err(), "{:.20}", query);
+            assert!(dictionary.search_snippets(query, None).is_err());
+            assert!(dictionary.count(query).is_err());
+        }
+        assert!(matches!(
+            dictionary.search(&nested, None),
+            Err(DictionaryError::InvalidQuery(_))
+        ));
+        let quoted = format!("\"{}\"", "(".repeat(100));
+        assert!(dictionary.search(&quoted, None).unwrap().is_empty());
+        assert_eq!(
+            "cyning",
+            dictionary.search("((king))", None).unwrap()[0].word
+        );
+    }
+
+    #[test]
+    fn test_iter_entries() {
+        init();
+        let mut dictionary = parse_str(FIXTURE).unwrap();
+        dictionary
+            .add_entry(Entry {
+                word: "stan".to_owned(),
+                definition: "stone".to_owned(),
+                ..Entry::default()
+            })
+            .unwrap();
+        dictionary.commit().unwrap();
+
+        let mut words: Vec<String> = dictionary
+            .iter_entries()
+            .map(|entry| entry.unwrap().word)
+            .collect();
+        words.sort();
+        assert_eq!(vec!["cyning", "leoht", "stan"], words);
+    }
+
+    #[test]
+    fn test_search_raw() {
+        init();
+        let dictionary = Dictionary::new(vec![
+            Entry {
+                part_of_speech: Some("m.".to_owned()),
+                ..entry("cyning", "king of the West Saxons")
+            },
+            Entry {
+                part_of_speech: Some("m.".to_owned()),
+                ..entry("cynedom", "a kingdom, the king of it")
+            },
+            Entry {
+                part_of_speech: Some("f.".to_owned()),
+                ..entry("cwen", "queen")
+            },
+        ])
+        .unwrap();
+        let words = |query: &str| -> Vec<String> {
+            let mut words: Vec<String> = dictionary
+                .search_raw(query, None)
+                .unwrap()
+                .into_iter()
+                .map(|e| e.word)
+                .collect();
+            words.sort();
+            words
+        };
+
+        assert_eq!(vec!["cyning"], words(r#"definition:"king of the""#));
+        assert_eq!(vec!["cynedom", "cyning"], words("definition:king"));
+        assert_eq!(vec!["cyning"], words(r#"definition:"king of th"*"#));
+        assert_eq!(vec!["cyning"], words(r#""west saxons""#));
+        assert_eq!(vec!["cwen"], words("part_of_speech:f."));
+        assert_eq!(vec!["cynedom"], words("king -word:cyning"));
+        assert_eq!(vec!["cwen", "cyning"], words("word:cyning OR queen"));
+        // Single-word prefixes
+        assert_eq!(vec!["cynedom", "cyning"], words("word:cyn*"));
+        assert_eq!(vec!["cynedom"], words("word:Cyn* -word:cyning"));
+        assert_eq!(vec!["cynedom", "cyning"], words("definition:kin*"));
+        assert_eq!(
+            vec!["cwen", "cynedom", "cyning"],
+            words("cwe* definition:k*")
+        );
+        assert_eq!(vec!["cwen"], words("part_of_speech:f*"));
+        assert_eq!(vec!["cynedom"], words("+cyn* +definition:kingdom"));
+        assert!(matches!(
+            dictionary.search_raw("word:cyn* OR queen", None),
+            Err(DictionaryError::InvalidQuery(_))
+        ));
+        assert!(matches!(
+            dictionary.search_raw("defnition:cyn*", None),
+            Err(DictionaryError::QueryParse { .. })
+        ));
+        assert!(matches!(
+            dictionary.search_raw("-word:cyn*", None),
+            Err(DictionaryError::QueryParse { .. })
+        ));
+        assert!(matches!(
+            dictionary.search_raw(r#"definition:"king"#, None),
+            Err(DictionaryError::QueryParse { .. })
+        ));
+    }
+
+    #[test]
+    fn test_search_snippets() {
+        init();
+        let dictionary = Dictionary::new(vec![
+            Entry {
+                word: "cyning".to_owned(),
+                definition: "a king, ruler".to_owned(),
+                ..Entry::default()
+            },
+            Entry {
+                word: "ruler".to_owned(),
+                definition: "a measuring stick".to_owned(),
+                ..Entry::default()
+            },
+        ])
+        .unwrap();
+
+        let results = dictionary.search_snippets("king", None).unwrap();
+        assert_eq!(1, results.len());
+        let (entry, snippet) = &results[0];
+        assert_eq!("cyning", entry.word);
+        assert_eq!("a king, ruler", snippet.fragment);
+        assert_eq!(vec![2..6], snippet.highlighted);
+        assert_eq!("king", &snippet.fragment[snippet.highlighted[0].clone()]);
+
+        let results = dictionary.search_snippets("ruler", None).unwrap();
+        assert_eq!(2, results.len());
+        let headword_only = results
+            .iter()
+            .find(|(entry, _)| entry.word == "ruler")
+            .unwrap();
+        assert_eq!(Snippet::default(), headword_only.1);
+    }
+
+    #[test]
+    fn test_stemming() {
+        init();
+        let entries = || {
+            vec![
+                Entry {
+                    word: "stan".to_owned(),
+                    definition: "stone".to_owned(),
+                    ..Entry::default()
+                },
+                Entry {
+                    word: "þegn".to_owned(),
+                    definition: "thane".to_owned(),
+                    ..Entry::default()
+                },
+            ]
+        };
+        let stemmed = Dictionary::new(entries()).unwrap();
+        for inflected in ["stanas", "stane", "stanum", "Stan"] {
+            let results = stemmed.search(inflected, None).unwrap();
+            assert_eq!(1, results.len(), "{inflected}");
+            assert_eq!("stan", results[0].word);
+        }
+        assert!(stemmed.search("thegnas", None).unwrap().is_empty());
+
+        let plain = DictionaryOptions {
+            stemming: false,
+            ..DictionaryOptions::default()
+        };
+        let plain = Dictionary::with_options(entries(), &plain).unwrap();
+        assert!(plain.search("stanas", None).unwrap().is_empty());
+
+        let folded = DictionaryOptions {
+            fold: true,
+            ..DictionaryOptions::default()
+        };
+        let folded = Dictionary::with_options(entries(), &folded).unwrap();
+        assert_eq!("þegn", folded.search("thegnas", None).unwrap()[0].word);
+    }
+
+    #[test]
+    fn test_dedup() {
+        init();
+        let entries = || {
+            vec![
+                entry("stan", "a stone"),
+                entry("stan", "a stone"),
+                entry("stan", "a rock"),
+                Entry {
+                    id: Some("c1".to_owned()),
+                    ..entry("cyning", "a king")
+                },
+                Entry {
+                    id: Some("c1".to_owned()),
+                    ..entry("cyning", "a ruler")
+                },
+                Entry {
+                    id: Some("c2".to_owned()),
+                    ..entry("cyning", "a king")
+                },
+                // Another headword of the same paragraph
+                Entry {
+                    id: Some("c1".to_owned()),
+                    ..entry("cyninge", "a king")
+                },
+            ]
+        };
+        assert_eq!(
+            7,
+            Dictionary::new(entries()).unwrap().iter_entries().count()
+        );
+
+        let options = DictionaryOptions {
+            dedup: true,
+            ..DictionaryOptions::default()
+        };
+        let dictionary = Dictionary::with_options(entries(), &options).unwrap();
+        let mut kept: Vec<_> = dictionary
+            .iter_entries()
+            .map(|e| {
+                let e = e.unwrap();
+                (e.word, e.definition, e.id)
+            })
+            .collect();
+        kept.sort();
+        let kept: Vec<_> = kept
+            .iter()
+            .map(|(word, definition, id)| (word.as_str(), definition.as_str(), id.as_deref()))
+            .collect();
+        assert_eq!(
+            vec![
+                ("cyning", "a king", Some("c1")),
+                ("cyning", "a king", Some("c2")),
+                ("cyninge", "a king", Some("c1")),
+                ("stan", "a rock", None),
+                ("stan", "a stone", None),
+            ],
+            kept
+        );
+    }
+
+    #[test]
+    fn test_word_boost() {
+        init();
+        let entries = || vec![entry("beorht", "light"), entry("light", "a lamp")];
+        let first = |dictionary: &Dictionary| dictionary.search("light", None).unwrap()[0].clone();
+
+        let mut dictionary = Dictionary::new(entries()).unwrap();
+        assert_eq!("light", first(&dictionary).word);
+        dictionary.set_word_boost(0.1);
+        assert_eq!("beorht", first(&dictionary).word);
+
+        let dictionary = Dictionary::builder()
+            .word_boost(0.1)
+            .build(entries())
+            .unwrap();
+        assert_eq!("beorht", first(&dictionary).word);
+    }
+
+    #[test]
+    fn test_length_normalization() {
+        init();
+        let entries = || {
+            vec![
+                entry("cyning", "a king, ruler or lord of a people or of a land"),
+                entry("cyng", "a king"),
+            ]
+        };
+        let scores = |dictionary: &Dictionary| {
+            let mut scores = dictionary.search_scored("king", None).unwrap();
+            scores.sort_by(|a, b| a.1.word.cmp(&b.1.word));
+            scores
+                .into_iter()
+                .map(|(score, _)| score)
+                .collect::<Vec<_>>()
+        };
+
+        let normalized = scores(&Dictionary::new(entries()).unwrap());
+        assert!(normalized[0] > normalized[1], "{:?}", normalized);
+        let flat = Dictionary::builder()
+            .length_normalization(false)
+            .build(entries())
+            .unwrap();
+        let flat = scores(&flat);
+        assert_eq!(flat[0], flat[1]);
+    }
+
+    #[test]
+    fn test_define_ignores_case() {
+        init();
+        let dictionary = Dictionary::new(vec![
+            entry("æsc", "a word"),
+            entry("Cyning", "a word"),
+            entry("gōd", "a word"),
+        ])
+        .unwrap();
+        let words = |results: Vec<Entry>| results.into_iter().map(|e| e.word).collect::<Vec<_>>();
+        for query in ["æsc", "Æsc", "ÆSC", "aesc", "AESC"] {
+            assert_eq!(vec!["æsc"], words(dictionary.define(query, None).unwrap()));
+        }
+        for query in ["cyning", "CYNING", "Cyning"] {
+            assert_eq!(
+                vec!["Cyning"],
+                words(dictionary.define(query, None).unwrap())
+            );
+        }
+        assert!(dictionary.define("god", None).unwrap().is_empty());
+        assert_eq!(vec!["gōd"], words(dictionary.define("GŌD", None).unwrap()));
+
+        assert!(dictionary.define_exact("cyning", None).unwrap().is_empty());
+        assert!(dictionary.define_exact("Æsc", None).unwrap().is_empty());
+        assert_eq!(
+            vec!["Cyning"],
+            words(dictionary.define_exact("Cyning", None).unwrap())
+        );
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_parse_report() {
+        init();
+        let (dictionary, report) = parse_str_with_report(FIXTURE).unwrap();
+        assert_eq!(2, dictionary.iter_entries().count());
+        assert_eq!(3, report.total_paragraphs);
+        assert_eq!(2, report.entries);
+        assert_eq!(1, report.skipped);
+        assert_eq!(1, report.warnings.len());
+
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(FIXTURE.as_bytes()).unwrap();
+        let (_, from_file) = parse_with_report(&file.path()).unwrap();
+        assert_eq!(report, from_file);
+    }
+
+    #[test]
+    fn test_progress() {
+        init();
+        let mut parsed = Vec::new();
+        let (entries, _) = entries_from_html_with_progress(FIXTURE, |progress| {
+            parsed.push(progress);
+        })
+        .unwrap();
+        assert_eq!(Some(&Progress::Parsed { done: 3, total: 3 }), parsed.last());
+
+        let mut indexed = Vec::new();
+        Dictionary::builder()
+            .build_with_progress(entries, |progress| indexed.push(progress))
+            .unwrap();
+        assert_eq!(
+            vec![
+                Progress::Indexed { done: 1, total: 2 },
+                Progress::Indexed { done: 2, total: 2 },
+            ],
+            indexed
+        );
+    }
+
+    #[test]
+    fn test_pos_filter() {
+        init();
+        let dictionary = Dictionary::new(vec![
+            Entry {
+                part_of_speech: Some("n.".to_owned()),
+                ..entry("scip", "a ship")
+            },
+            Entry {
+                part_of_speech: Some("m.".to_owned()),
+                ..entry("scip", "a ship, vessel")
+            },
+            entry("scip", "shape"),
+        ])
+        .unwrap();
+        let definitions = |results: Vec<Entry>| {
+            results
+                .into_iter()
+                .map(|e| e.definition)
+                .collect::<Vec<_>>()
+        };
+
+        let masculine = dictionary
+            .search_with_pos("scip", Some("m."), None)
+            .unwrap();
+        assert_eq!(vec!["a ship, vessel"], definitions(masculine));
+        let neuter = dictionary
+            .define_with_pos("scip", Some("n."), None)
+            .unwrap();
+        assert_eq!(vec!["a ship"], definitions(neuter));
+        assert_eq!(
+            3,
+            dictionary
+                .search_with_pos("scip", None, None)
+                .unwrap()
+                .len()
+        );
+        assert!(dictionary
+            .define_with_pos("scip", Some("xyz."), None)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_ordinal() {
+        init();
+        let entries = entries_from_html(FIXTURE).unwrap();
+        assert_eq!(
+            vec![Some(0), Some(1)],
+            entries.iter().map(|e| e.ordinal).collect::<Vec<_>>()
+        );
+        assert_eq!(entries, entries_from_html(FIXTURE).unwrap());
+
+        let mut dictionary = Dictionary::new(entries.into_iter().rev().collect()).unwrap();
+        dictionary
+            .add_entry(Entry {
+                word: "leohtlic".to_owned(),
+                definition: "light, bright".to_owned(),
+                ..Entry::default()
+            })
+            .unwrap();
+        dictionary.commit().unwrap();
+        let words = |entries: Vec<Entry>| entries.into_iter().map(|e| e.word).collect::<Vec<_>>();
+        assert_eq!(
+            vec!["cyning", "leoht", "leohtlic"],
+            words(dictionary.entries_in_source_order().unwrap())
+        );
+        assert_eq!(
+            vec!["cyning", "leoht", "leohtlic"],
+            words(
+                dictionary
+                    .search_sorted("king OR light", None, SortOrder::Source)
+                    .unwrap()
+            )
+        );
+    }
+
+    #[test]
+    fn test_entries_with_length() {
+        let dictionary = Dictionary::new(vec![
+            entry("cyning", "king, ruler"),
+            entry("leoht", "light"),
+            entry("stan", "stān"),
+            entry("ǣ", ""),
+        ])
+        .unwrap();
+        let words = |min, max| {
+            dictionary
+                .entries_with_length(min, max)
+                .unwrap()
+                .into_iter()
+                .map(|e| e.word)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(vec!["ǣ"], words(0, 0));
+        // "stān" is four characters though five bytes
+        assert_eq!(vec!["stan", "leoht"], words(1, 5));
+        assert_eq!(vec!["ǣ", "stan", "leoht", "cyning"], words(0, 100));
+        assert!(words(6, 10).is_empty());
+    }
+
+    #[test]
+    fn test_empty_definitions() {
+        init();
+        let html = r#"<html><body>
+            <p><a id="word_1"></a><b>cyning</b><i>king</i></p>
+            <p><a id="word_2"></a><b>leoht</b>   </p>
+            <p><a id="word_3"></a><b>stan</b> m. </p>
+            <p><a id="word_4"></a><b> </b><i>nothing</i></p>
+        </body></html>"#;
+
+        let (entries, report) = entries_from_html_with_report(html).unwrap();
+        assert_eq!(
+            vec!["cyning"],
+            entries.iter().map(|e| &e.word).collect::<Vec<_>>()
+        );
+        assert_eq!(3, report.skipped);
+        assert!(
+            report.warnings[0].contains("leoht"),
+            "{:?}",
+            report.warnings
+        );
+
+        let options = ParseOptions {
+            skip_empty_definitions: false,
+            ..ParseOptions::default()
+        };
+        let entries: Vec<Entry> = Entries::from_html(html)
+            .options(options)
+            .filter_map(Result::ok)
+            .collect();
+        assert_eq!(
+            vec![("cyning", "king"), ("leoht", ""), ("stan", "")],
+            entries
+                .iter()
+                .map(|e| (e.word.as_str(), e.definition.as_str()))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(Some("m."), entries[2].part_of_speech.as_deref());
+
+        let selected = parse_str_with_selector(
+            "<dl><dt>cyning</dt><dd>king</dd><dt>leoht</dt><dd> </dd></dl>",
+            "dt",
+            "dt",
+        )
+        .unwrap();
+        assert_eq!(1, selected.iter_entries().count());
+    }
+
+    #[test]
+    fn test_source() {
+        init();
+        let label = |source: &str| ParseOptions {
+            source: Some(source.to_owned()),
+            ..ParseOptions::default()
+        };
+        let mut entries = Entries::from_html(FIXTURE)
+            .options(label("bosworth-toller"))
+            .filter_map(Result::ok)
+            .collect::<Vec<_>>();
+        let clark_hall = parse_str_with_selector_and_options(
+            "<dl><dt>cyning</dt><dd>king, ruler</dd></dl>",
+            "dt",
+            "dt",
+            &label("clark-hall"),
+        )
+        .unwrap();
+        let clark_hall = clark_hall
+            .iter_entries()
+            .collect::<crate::Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(Some("clark-hall"), clark_hall[0].source.as_deref());
+        entries.extend(clark_hall);
+        let dictionary = Dictionary::try_from(entries).unwrap();
+
+        assert_eq!(2, dictionary.search("king", None).unwrap().len());
+        let found = dictionary
+            .search_with_source("king", Some("clark-hall"), None)
+            .unwrap();
+        assert_eq!(
+            vec!["king, ruler"],
+            found
+                .iter()
+                .map(|e| e.definition.as_str())
+                .collect::<Vec<_>>()
+        );
+        let found = dictionary
+            .search_with_source("king", Some("bosworth-toller"), None)
+            .unwrap();
+        assert_eq!(1, found.len());
+        assert_eq!(Some("bosworth-toller"), found[0].source.as_deref());
+        assert!(dictionary
+            .search_with_source("king", Some("sweet"), None)
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            2,
+            dictionary
+                .search_with_source("king", None, None)
+                .unwrap()
+                .len()
+        );
+    }
+
+    #[test]
+    fn test_lookup() {
+        init();
+        let dictionary = parse_str(
+            r#"<p><a id="word_1"></a><b>cyning</b> king</p>
+            <p><a id="word_2"></a><b>cyninge</b> queen</p>
+            <p><a id="word_3"></a><b>cynig</b> king, a misprint</p>
+            <p><a id="word_4"></a><b>stan</b> a stone</p>"#,
+        )
+        .unwrap();
+        let lookup = |term, limit| -> Vec<(MatchKind, String)> {
+            dictionary
+                .lookup(term, limit)
+                .unwrap()
+                .into_iter()
+                .map(|(kind, entry)| (kind, entry.word))
+                .collect()
+        };
+        let results = lookup("Cyning", None);
+        assert_eq!((MatchKind::Exact, "cyning".to_owned()), results[0]);
+        assert_eq!(3, results.len(), "{results:?}");
+        assert!(results[1..]
+            .iter()
+            .all(|(kind, _)| *kind == MatchKind::Fuzzy));
+        assert_eq!(1, lookup("cyning", Some(1)).len());
+        // Nothing matches exactly, but there are close headwords
+        assert_eq!(
+            vec![(MatchKind::Fuzzy, "cyning".to_owned())],
+            lookup("cyningg", None)
+                .into_iter()
+                .filter(|(_, word)| word == "cyning")
+                .collect::<Vec<_>>()
+        );
+        // Short terms only allow one edit
+        assert_eq!(
+            vec![(MatchKind::Fuzzy, "stan".to_owned())],
+            lookup("sta", None)
+        );
+        assert!(lookup("st", None).is_empty());
+    }
+
+    #[test]
+    fn test_define_one() {
+        init();
+        let dictionary = parse_str(FIXTURE).unwrap();
+        assert_eq!(
+            "king",
+            dictionary.define_one("CYNING").unwrap().unwrap().definition
+        );
+        assert_eq!(None, dictionary.define_one("cyn").unwrap());
+    }
+
+    #[test]
+    fn test_define_any() {
+        init();
+        let html = r#"<html><body>
+            <p><a id="word_1"></a><b>cyning</b>, <b>cining</b> m. king</p>
+            <p><a id="word_2"></a><b>cyng</b> m. king</p>
+            <p><a id="word_3"></a><b>leoht</b> n. light</p>
+            </body></html>"#;
+        let dictionary = parse_str(html).unwrap();
+        let mut found = dictionary
+            .define_any(&["Cyning", "cining", "cyning", "cyng", "kyning"])
+            .unwrap()
+            .into_iter()
+            .map(|entry| (entry.word, entry.id.unwrap()))
+            .collect::<Vec<_>>();
+        found.sort();
+        let found: Vec<_> = found
+            .iter()
+            .map(|(word, id)| (word.as_str(), id.as_str()))
+            .collect();
+        assert_eq!(
+            vec![
+                ("cining", "word_1"),
+                ("cyng", "word_2"),
+                ("cyning", "word_1")
+            ],
+            found
+        );
+        assert!(dictionary.define_any(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_search_definition() {
+        let dictionary = Dictionary::new(vec![
+            entry("beorn", "a man, warrior, hero"),
+            entry("cempa", "a warrior, soldier"),
+            entry("warrior", "not a real headword"),
+        ])
+        .unwrap();
+
+        let words = |results: Vec<Entry>| results.into_iter().map(|e| e.word).collect::<Vec<_>>();
+        let mut glossed = words(dictionary.search_definition("warrior", None).unwrap());
+        glossed.sort();
+        assert_eq!(vec!["beorn", "cempa"], glossed);
+        assert_eq!(3, dictionary.search("warrior", None).unwrap().len());
+    }
+
+    #[test]
+    fn test_get_by_id() {
+        init();
+        let dictionary = parse_str(FIXTURE).unwrap();
+        assert_eq!(
+            "leoht",
+            dictionary.get_by_id("word_2").unwrap().unwrap().word
+        );
+        assert_eq!(
+            "cyning",
+            dictionary.get_by_id("#word_1").unwrap().unwrap().word
+        );
+        assert_eq!(None, dictionary.get_by_id("word_3").unwrap());
+        assert_eq!(None, dictionary.get_by_id("word").unwrap());
+    }
+
+    #[test]
+    fn test_len() {
+        init();
+        let dictionary = parse_str(FIXTURE).unwrap();
+        assert_eq!(2, dictionary.len());
+        assert!(!dictionary.is_empty());
+        let empty = Dictionary::builder().build(Vec::new()).unwrap();
+        assert_eq!(0, empty.len());
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn test_concurrent_queries() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Dictionary>();
+
+        init();
+        let dictionary = std::sync::Arc::new(parse_str(FIXTURE).unwrap());
+        let threads = (0..8)
+            .map(|i| {
+                let dictionary = std::sync::Arc::clone(&dictionary);
+                std::thread::spawn(move || {
+                    for _ in 0..200 {
+                        let (word, gloss) = if i % 2 == 0 {
+                            ("cyning", "king")
+                        } else {
+                            ("leoht", "light")
+                        };
+                        let found = dictionary.search(gloss, None).unwrap();
+                        assert_eq!(word, found[0].word);
+                        assert_eq!(1, found.len());
+                        let defined = dictionary.define(word, None).unwrap();
+                        assert_eq!(word, defined[0].word);
+                        assert_eq!(2, dictionary.iter_entries().count());
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_split_senses() {
+        assert_eq!(vec!["a king.", "God."], split_senses("1. a king. 2. God."));
+        assert_eq!(
+            vec!["light", "brightness (of day; of a lamp)", "the world"],
+            split_senses("light; brightness (of day; of a lamp); the world")
+        );
+        assert_eq!(
+            vec!["a ruler:", "a king", "God"],
+            split_senses("a ruler: 1. a king; 2. God")
+        );
+        // Numbers that don't start a clause, or don't count up from 1, aren't senses
+        assert_eq!(
+            vec!["a king who died in 871. 2. kings"],
+            split_senses("a king who died in 871. 2. kings")
+        );
+        assert_eq!(vec!["chapter 1. of 2"], split_senses("chapter 1. of 2"));
+        assert_eq!(vec!["1. a king"], split_senses("1. a king"));
+        assert_eq!(vec!["king"], split_senses("king"));
+        assert!(split_senses("").is_empty());
+
+        let html = r#"<p><a id="word_1"></a><b>cyning</b> m. 1. a king. 2. God.</p>"#;
+        let options = ParseOptions {
+            split_senses: true,
+            ..ParseOptions::default()
+        };
+        let dictionary = parse_str_with_options(html, &options).unwrap();
+        let entry = &dictionary.define("cyning", None).unwrap()[0];
+        assert_eq!("1. a king. 2. God.", entry.definition);
+        assert_eq!(vec!["a king.", "God."], entry.senses);
+        let plain = parse_str(html).unwrap();
+        assert!(plain.define("cyning", None).unwrap()[0].senses.is_empty());
+
+        // Senses are split from the definition as normalized
+        let html = r#"<p><a id="word_1"></a><b>ƿer</b> m. 1. a man. 2. a ƿer-gild.</p>"#;
+        let options = ParseOptions {
+            split_senses: true,
+            normalize_wynn: true,
+            ..ParseOptions::default()
+        };
+        let dictionary = parse_str_with_options(html, &options).unwrap();
+        let entry = &dictionary.define("wer", None).unwrap()[0];
+        assert_eq!("1. a man. 2. a wer-gild.", entry.definition);
+        assert_eq!(vec!["a man.", "a wer-gild."], entry.senses);
+    }
+
+    #[test]
+    fn test_explain() {
+        init();
+        let dictionary = parse_str(FIXTURE).unwrap();
+        let explanation: serde_json::Value =
+            serde_json::from_str(&dictionary.explain("king", "cyning").unwrap()).unwrap();
+        let score = dictionary.search_scored("king", None).unwrap()[0].0;
+        assert_eq!(
+            score,
+            explanation["value"].as_f64().unwrap() as f32,
+            "{}",
+            explanation
+        );
+        assert!(matches!(
+            dictionary.explain("king", "leoht"),
+            Err(DictionaryError::NoMatch(word)) if word == "leoht"
+        ));
+        assert!(matches!(
+            dictionary.explain("king", "stan"),
+            Err(DictionaryError::NoMatch(_))
+        ));
+    }
+
+    #[test]
+    fn test_transliterate_futhorc() {
+        let row = [
+            ("ᚠ", "f"),
+            ("ᚢ", "u"),
+            ("ᚦ", "þ"),
+            ("ᚩ", "o"),
+            ("ᚱ", "r"),
+            ("ᚳ", "c"),
+            ("ᚷ", "g"),
+            ("ᚹ", "w"),
+            ("ᚻ", "h"),
+            ("ᚾ", "n"),
+            ("ᛁ", "i"),
+            ("ᛄ", "j"),
+            ("ᛇ", "eo"),
+            ("ᛈ", "p"),
+            ("ᛉ", "x"),
+            ("ᛋ", "s"),
+            ("ᛏ", "t"),
+            ("ᛒ", "b"),
+            ("ᛖ", "e"),
+            ("ᛗ", "m"),
+            ("ᛚ", "l"),
+            ("ᛝ", "ng"),
+            ("ᛟ", "œ"),
+            ("ᛞ", "d"),
+            ("ᚪ", "a"),
+            ("ᚫ", "æ"),
+            ("ᚣ", "y"),
+            ("ᛠ", "ea"),
+        ];
+        for (rune, latin) in row {
+            assert!(is_runic(rune));
+            assert_eq!(latin, transliterate_futhorc(rune), "{}", rune);
+        }
+        assert_eq!("god", transliterate_futhorc("ᚷᚩᛞ"));
+        assert_eq!("stan cyning", transliterate_futhorc("ᛋᛏᚪᚾ᛫ᚳᚣᚾᛁᛝ"));
+        assert_eq!("god", transliterate_futhorc("god"));
+        assert!(!is_runic("god"));
+
+        let dictionary =
+            Dictionary::new(vec![entry("god", "a word"), entry("gōd", "a word")]).unwrap();
+        let results = dictionary
+            .define(&transliterate_futhorc("ᚷᚩᛞ"), None)
+            .unwrap();
+        assert_eq!(vec![entry("god", "a word")], results);
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_writer_heap() {
+        init();
+        let options = |writer_heap| DictionaryOptions {
+            writer_heap,
+            ..DictionaryOptions::default()
+        };
+        let entries = || entries_from_html(FIXTURE).unwrap();
+        let mut dictionary =
+            Dictionary::with_options(entries(), &options(MIN_WRITER_HEAP)).unwrap();
+        dictionary.extend(entries()).unwrap();
+        dictionary.commit().unwrap();
+
+        for writer_heap in [0, MIN_WRITER_HEAP - 1, MAX_WRITER_HEAP + 1] {
+            let result = Dictionary::with_options(entries(), &options(writer_heap));
+            assert!(matches!(
+                result,
+                Err(DictionaryError::InvalidWriterHeap(n)) if n == writer_heap
+            ));
+        }
+        let dir = tempfile::tempdir().unwrap();
+        let result = Dictionary::create_in_dir_with_options(dir.path(), entries(), &options(0));
+        assert!(matches!(result, Err(DictionaryError::InvalidWriterHeap(0))));
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_builder() {
+        init();
+        let entries = || {
+            ["stan", "stan", "þegn"]
+                .into_iter()
+                .map(|word| Entry {
+                    word: word.to_owned(),
+                    definition: "a word".to_owned(),
+                    ..Entry::default()
+                })
+                .collect::<Vec<_>>()
+        };
+        let dictionary = Dictionary::builder()
+            .folding(true)
+            .dedup(true)
+            .writer_heap(MIN_WRITER_HEAP)
+            .build(entries())
+            .unwrap();
+        assert_eq!(2, dictionary.iter_entries().count());
+        assert_eq!("þegn", dictionary.search("thegn", None).unwrap()[0].word);
+
+        let plain = Dictionary::builder()
+            .stemming(false)
+            .build(entries())
+            .unwrap();
+        assert!(plain.search("stanas", None).unwrap().is_empty());
+        assert_eq!(2, plain.search("stan", None).unwrap().len());
+
+        let dir = tempfile::tempdir().unwrap();
+        drop(
+            Dictionary::builder()
+                .in_dir(dir.path())
+                .build(entries())
+                .unwrap(),
+        );
+        assert_eq!(
+            3,
+            Dictionary::open(dir.path()).unwrap().iter_entries().count()
+        );
+
+        let result = Dictionary::builder().writer_heap(0).build(entries());
+        assert!(matches!(result, Err(DictionaryError::InvalidWriterHeap(0))));
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn test_parse_url_blocking() {
+        use std::io::{BufRead, Write};
+
+        init();
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/dictionary.html", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            // Read the request headers up to the blank line that ends them
+            let mut request = std::io::BufReader::new(&stream);
+            let mut line = String::new();
+            while request.read_line(&mut line).unwrap() > 2 {
+                line.clear();
+            }
+            let mut encoder =
+                flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(FIXTURE.as_bytes()).unwrap();
+            let body = encoder.finish().unwrap();
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Encoding: gzip\r\nContent-Length: {}\r\n\r\n",
+                body.len()
+            )
+            .unwrap();
+            stream.write_all(&body).unwrap();
+        });
+
+        let dictionary = crate::parse_url_blocking(url).unwrap();
+        server.join().unwrap();
+        assert_eq!(1, dictionary.define("cyning", None).unwrap().len());
+    }
+
+    #[cfg(feature = "server")]
+    #[tokio::test]
+    async fn test_serve() {
+        init();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let dictionary = parse_str(FIXTURE).unwrap();
+        tokio::spawn(crate::server::serve_on(dictionary, listener));
+
+        let get = |path: &str| {
+            let url = format!("{}{}", url, path);
+            async move {
+                let response = reqwest::get(url).await.unwrap();
+                let status = response.status().as_u16();
+                let body = response.text().await.unwrap();
+                (
+                    status,
+                    serde_json::from_str::<serde_json::Value>(&body).unwrap(),
+                )
+            }
+        };
+        let (status, body) = get("/define?q=cyning").await;
+        assert_eq!(200, status);
+        assert_eq!("cyning", body[0]["word"]);
+
+        let (status, body) = get("/search?q=king&limit=1").await;
+        assert_eq!(200, status);
+        assert_eq!(1, body.as_array().unwrap().len());
+
+        let (status, body) = get("/search?q=word:(king").await;
+        assert_eq!(400, status);
+        assert!(body["error"].as_str().unwrap().starts_with("Invalid query"));
+
+        let response = reqwest::get(format!("{}/search", url)).await.unwrap();
+        assert_eq!(400, response.status().as_u16());
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_export_stardict() {
+        init();
+        let dictionary = Dictionary::new(vec![
+            Entry {
+                part_of_speech: Some("m.".to_owned()),
+                ..entry("stan", "a stone")
+            },
+            entry("æsc", "an ash tree"),
+            entry("Cyning", "a king"),
+            entry("ac", "an oak"),
+        ])
+        .unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        dictionary.export_stardict(dir.path()).unwrap();
+
+        let read = |extension: &str| {
+            std::fs::read(
+                dir.path()
+                    .join(format!("anglo-saxon-dictionary.{}", extension)),
+            )
+            .unwrap()
+        };
+        let (ifo, idx, dict) = (read("ifo"), read("idx"), read("dict"));
+        let ifo = String::from_utf8(ifo).unwrap();
+        assert!(ifo.starts_with("StarDict's dict ifo file\nversion=2.4.2\n"));
+        assert!(ifo.contains(&format!("\nidxfilesize={}\n", idx.len())));
+
+        let mut rest = &idx[..];
+        let mut definitions = Vec::new();
+        while !rest.is_empty() {
+            let nul = rest.iter().position(|&b| b == 0).unwrap();
+            let word = std::str::from_utf8(&rest[..nul]).unwrap().to_owned();
+            let number = |at: usize| {
+                u32::from_be_bytes(rest[nul + at..nul + at + 4].try_into().unwrap()) as usize
+            };
+            let (offset, size) = (number(1), number(5));
+            let definition = std::str::from_utf8(&dict[offset..offset + size]).unwrap();
+            definitions.push((word, definition.to_owned()));
+            rest = &rest[nul + 9..];
+        }
+        assert_eq!(
+            vec![
+                ("ac".to_owned(), "an oak".to_owned()),
+                ("Cyning".to_owned(), "a king".to_owned()),
+                ("stan".to_owned(), "(m.) a stone".to_owned()),
+                ("æsc".to_owned(), "an ash tree".to_owned()),
+            ],
+            definitions
+        );
+        assert!(ifo.contains("\nwordcount=4\n"));
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn test_export_sqlite() {
+        init();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("dictionary.sqlite");
+        let dictionary = parse_str(FIXTURE).unwrap();
+        dictionary.export_sqlite(&path).unwrap();
+
+        let connection = rusqlite::Connection::open(&path).unwrap();
+        let count: usize = connection
+            .query_row("SELECT count(*) FROM entries", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(dictionary.iter_entries().count(), count);
+        let (definition, id): (String, Option<String>) = connection
+            .query_row(
+                "SELECT definition, id FROM entries WHERE word = 'cyning'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!("king", definition);
+        assert_eq!(Some("word_1".to_owned()), id);
+
+        assert!(matches!(
+            dictionary.export_sqlite(&path),
+            Err(DictionaryError::Sqlite(_))
+        ));
+        let count: usize = connection
+            .query_row("SELECT count(*) FROM entries", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(dictionary.iter_entries().count(), count);
+    }
+
+    #[test]
+    fn test_add_entry() {
+        init();
+        let mut dictionary = parse_str(FIXTURE).unwrap();
+        let stan = Entry {
+            word: "stan".to_owned(),
+            definition: "stone".to_owned(),
+            ..Entry::default()
+        };
+        dictionary.add_entry(stan.clone()).unwrap();
+        assert!(dictionary.define("stan", None).unwrap().is_empty());
+
+        dictionary.commit().unwrap();
+        assert_eq!(vec![stan], dictionary.define("stan", None).unwrap());
+        assert_eq!(1, dictionary.define("cyning", None).unwrap().len());
+
+        dictionary
+            .extend(vec![Entry {
+                word: "ac".to_owned(),
+                definition: "oak".to_owned(),
+                ..Entry::default()
+            }])
+            .unwrap();
+        dictionary.commit().unwrap();
+        assert_eq!(1, dictionary.search("oak", None).unwrap().len());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_from_json_lines() {
+        init();
+        let lines = r#"{"word":"cyning","definition":"king, ruler","part_of_speech":"m."}
+
+{"word":"leoht","definition":"light"}
+{"word":"stan","definition":
+"#;
+        let err = Dictionary::from_json_lines(lines.as_bytes()).err().unwrap();
+        assert!(
+            matches!(err, DictionaryError::Json { line: 4, .. }),
+            "{err:?}"
+        );
+
+        let dictionary = Dictionary::from_json_lines_lossy(lines.as_bytes()).unwrap();
+        let cyning = &dictionary.define("cyning", None).unwrap()[0];
+        assert_eq!(Some("m."), cyning.part_of_speech.as_deref());
+        assert_eq!(1, dictionary.define("leoht", None).unwrap().len());
+        assert!(dictionary.define("stan", None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_export_wordlist() {
+        init();
+        let dictionary = Dictionary::new(
+            ["stan", "ǣsc", "cyning", "æsc", "cyning", "þeod", "ac"]
+                .into_iter()
+                .map(|word| entry(word, "a word"))
+                .collect(),
+        )
+        .unwrap();
+        let wordlist = |normalize| {
+            let mut out = Vec::new();
+            dictionary.export_wordlist(&mut out, normalize).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+        assert_eq!("ac\næsc\nǣsc\ncyning\nstan\nþeod\n", wordlist(false));
+        assert_eq!("ac\naesc\ncyning\nstan\ntheod\n", wordlist(true));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_export_json() {
+        init();
+        let dictionary = parse_str(FIXTURE).unwrap();
+        let entries = dictionary.entries_in_source_order().unwrap();
+
+        let mut lines = Vec::new();
+        dictionary.export_json_lines(&mut lines).unwrap();
+        let lines = String::from_utf8(lines).unwrap();
+        let header: ExportHeader = serde_json::from_str(lines.lines().next().unwrap()).unwrap();
+        assert_eq!(ExportHeader::current(), header);
+        assert_eq!(EXPORT_FORMAT_VERSION, header.format_version);
+        assert!(header.fields.contains(&"definition".to_owned()));
+        let imported = Dictionary::from_json_lines(lines.as_bytes()).unwrap();
+        assert_eq!(entries, imported.entries_in_source_order().unwrap());
+
+        let mut json = Vec::new();
+        dictionary.export_json(&mut json).unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(EXPORT_FORMAT_VERSION, value["format_version"]);
+        assert_eq!(entries.len(), value["entries"].as_array().unwrap().len());
+        let imported = Dictionary::from_json(json.as_slice()).unwrap();
+        assert_eq!(entries, imported.entries_in_source_order().unwrap());
+
+        // Every field an entry serializes is listed in the header
+        let entry = &imported.entries_in_source_order().unwrap()[0];
+        let mut full = entry.clone();
+        full.original_word = Some("cyniƿ".to_owned());
+        full.see_also = vec!["cyne".to_owned()];
+        full.senses = vec!["king".to_owned()];
+        full.source = Some("bosworth-toller".to_owned());
+        full.grammar = Grammar::from_abbreviation("m.");
+        for field in serde_json::to_value(&full)
+            .unwrap()
+            .as_object()
+            .unwrap()
+            .keys()
+        {
+            assert!(header.fields.contains(field), "{field} isn't in the header");
+        }
+
+        // Other format versions are refused, before their entries are read
+        let future = lines.replacen(
+            &format!("\"format_version\":{}", EXPORT_FORMAT_VERSION),
+            "\"format_version\":2",
+            1,
+        );
+        let err = Dictionary::from_json_lines(future.as_bytes())
+            .err()
+            .unwrap();
+        assert!(
+            matches!(err, DictionaryError::IncompatibleExport(2)),
+            "{err:?}"
+        );
+        assert_eq!(
+            "Export format version 2 isn't supported; expected version 1",
+            err.to_string()
+        );
+        let future = serde_json::json!({"format_version": 2, "fields": [], "entries": [{}]});
+        let err = Dictionary::from_json(future.to_string().as_bytes())
+            .err()
+            .unwrap();
+        assert!(
+            matches!(err, DictionaryError::IncompatibleExport(2)),
+            "{err:?}"
+        );
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_reload() {
+        init();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("index");
+        drop(Dictionary::create_in_dir(&path, vec![entry("cyning", "king")]).unwrap());
+
+        let reader = Dictionary::open(&path).unwrap();
+        assert_eq!(1, reader.len());
+        // Another handle on the same directory stands in for the process rebuilding it
+        let mut writer = Dictionary::open(&path).unwrap();
+        writer.add_entry(entry("cwen", "queen")).unwrap();
+        writer.commit().unwrap();
+
+        reader.reload().unwrap();
+        assert_eq!(2, reader.len());
+        assert_eq!("queen", reader.define("cwen", None).unwrap()[0].definition);
+        // Reloading with nothing new committed is harmless
+        reader.reload().unwrap();
+        assert_eq!(2, reader.len());
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_persist_index() {
+        init();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("index");
+        let entries = vec![Entry {
+            word: "cyning".to_owned(),
+            definition: "king".to_owned(),
+            part_of_speech: Some("m.".to_owned()),
+            id: Some("word_1".to_owned()),
+            grammar: Grammar::from_abbreviation("sm."),
+            see_also: vec!["cyne".to_owned(), "cynn".to_owned()],
+            original_word: Some("cyniƿ".to_owned()),
+            ordinal: Some(7),
+            senses: vec!["king".to_owned()],
+            source: Some("bosworth-toller".to_owned()),
+        }];
+        let created = Dictionary::create_in_dir(&path, entries.clone()).unwrap();
+        assert_eq!(entries, created.define("cyning", None).unwrap());
+        drop(created);
+
+        let opened = Dictionary::open(&path).unwrap();
+        assert_eq!(entries, opened.define("cyning", None).unwrap());
+        assert!(Dictionary::create_in_dir(&path, entries).is_err());
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_open_invalid_index() {
+        init();
+        let dir = tempfile::tempdir().unwrap();
+        let missing = Dictionary::open(dir.path().join("missing")).err().unwrap();
+        assert!(missing.to_string().contains("is not an index directory"));
+        let empty = Dictionary::open(dir.path()).err().unwrap();
+        assert!(empty.to_string().contains("No index found"));
+    }
 }
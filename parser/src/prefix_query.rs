@@ -0,0 +1,114 @@
+//! Single-word prefix clauses in raw queries, e.g. `word:cyn*`, which tantivy's query grammar
+//! only understands for phrases of at least two words.
+
+use crate::{DictionaryError, Result};
+use tantivy::query::Occur;
+
+/// A top-level clause of a raw query.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Clause<'a> {
+    /// `field:prefix*`, or `prefix*` for the default fields.
+    Prefix {
+        occur: Occur,
+        field: Option<&'a str>,
+        prefix: &'a str,
+    },
+    /// Anything else, left to the query parser.
+    Other { occur: Occur, text: &'a str },
+}
+
+/// Split `query` into its top-level clauses, if any of them is a single-word prefix. None when
+/// there's no such prefix, so the query parser can take the query whole. The clauses must be
+/// independent of each other for the prefixes to be taken out, so a query mixing a prefix
+/// with `AND`, `OR` or parentheses is refused.
+pub(crate) fn clauses(query: &str) -> Result<Option<Vec<Clause<'_>>>> {
+    let tokens = tokens(query);
+    let clauses: Vec<Clause> = tokens.iter().map(|token| clause(token)).collect();
+    if !clauses
+        .iter()
+        .any(|clause| matches!(clause, Clause::Prefix { .. }))
+    {
+        return Ok(None);
+    }
+    let combined = tokens
+        .iter()
+        .any(|&token| token == "AND" || token == "OR" || unquoted(token).any(|c| c == '('));
+    if combined {
+        return Err(DictionaryError::InvalidQuery(
+            "a single-word prefix such as word:cyn* can't be combined with AND, OR or \
+             parentheses"
+                .to_owned(),
+        ));
+    }
+    Ok(Some(clauses))
+}
+
+/// The whitespace-separated tokens of `query`, keeping quoted phrases whole.
+fn tokens(query: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let (mut start, mut quoted, mut escaped) = (None, false, false);
+    for (i, c) in query.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if let Some(start) = start.take() {
+                    tokens.push(&query[start..i]);
+                }
+                continue;
+            }
+            _ => {}
+        }
+        start.get_or_insert(i);
+    }
+    tokens.extend(start.map(|start| &query[start..]));
+    tokens
+}
+
+/// The characters of `token` outside quotes.
+fn unquoted(token: &str) -> impl Iterator<Item = char> + '_ {
+    let mut quoted = false;
+    token.chars().filter(move |&c| {
+        if c == '"' {
+            quoted = !quoted;
+        }
+        !quoted && c != '"'
+    })
+}
+
+fn clause(token: &str) -> Clause<'_> {
+    let (occur, text) = if let Some(rest) = token.strip_prefix('+').filter(|r| !r.is_empty()) {
+        (Occur::Must, rest)
+    } else if let Some(rest) = token.strip_prefix('-').filter(|r| !r.is_empty()) {
+        (Occur::MustNot, rest)
+    } else {
+        (Occur::Should, token)
+    };
+    let prefix = text
+        .strip_suffix('*')
+        .and_then(|term| match term.split_once(':') {
+            Some((field, prefix)) => {
+                let named =
+                    !field.is_empty() && field.chars().all(|c| c.is_alphanumeric() || c == '_');
+                named.then_some((Some(field), prefix))
+            }
+            None => Some((None, term)),
+        });
+    match prefix {
+        Some((field, prefix)) if is_word(prefix) => Clause::Prefix {
+            occur,
+            field,
+            prefix,
+        },
+        _ => Clause::Other { occur, text },
+    }
+}
+
+/// Whether `prefix` is a bare word, without the syntax of phrases, groups or boosts.
+fn is_word(prefix: &str) -> bool {
+    !prefix.is_empty()
+        && !prefix.contains(|c: char| {
+            c.is_whitespace() || matches!(c, '"' | '*' | ':' | '(' | ')' | '^' | '~' | '\\')
+        })
+}
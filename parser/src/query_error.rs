@@ -0,0 +1,113 @@
+use crate::DictionaryError;
+use tantivy::query::QueryParserError;
+use tantivy::query_grammar::parse_query_lenient;
+
+/// The fields a query may name, e.g. `definition:king`. The others are internal to the index.
+const QUERY_FIELDS: &[&str] = &[
+    "word",
+    "definition",
+    "part_of_speech",
+    "word_class",
+    "gender",
+    "inflection",
+    "id",
+    "see_also",
+    "original_word",
+    "source",
+];
+
+/// How deeply a query may nest parentheses. tantivy's query grammar recurses for each level,
+/// so a query nested thousands deep would overflow the stack rather than fail to parse.
+pub(crate) const MAX_DEPTH: usize = 32;
+
+/// The deepest nesting of parentheses in `query`, not counting those within quotes.
+pub(crate) fn nesting_depth(query: &str) -> usize {
+    let (mut depth, mut deepest, mut quoted, mut escaped) = (0usize, 0, false, false);
+    for c in query.chars() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => quoted = !quoted,
+            '(' if !quoted => {
+                depth += 1;
+                deepest = deepest.max(depth);
+            }
+            ')' if !quoted => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    deepest
+}
+
+/// Describe what's wrong with `query` for someone writing it by hand, e.g. "unknown field
+/// 'defnition', did you mean 'definition'?", with the position of the offending token where
+/// the query grammar reports one.
+pub(crate) fn describe(error: &QueryParserError, query: &str) -> String {
+    match error {
+        QueryParserError::SyntaxError(_) => {
+            let (_, errors) = parse_query_lenient(query);
+            match errors.first() {
+                // The grammar escapes the delimiter it expected, as in `missing delimiter \"`
+                Some(error) => format!(
+                    "{} at position {}",
+                    error.message.replace('\\', ""),
+                    error.pos
+                ),
+                None => "couldn't parse the query".to_owned(),
+            }
+        }
+        QueryParserError::FieldDoesNotExist(field) => {
+            let closest = QUERY_FIELDS
+                .iter()
+                .map(|known| (edit_distance(field, known), known))
+                .min()
+                .filter(|(distance, _)| *distance <= 2);
+            match closest {
+                Some((_, known)) => {
+                    format!("unknown field '{}', did you mean '{}'?", field, known)
+                }
+                None => format!(
+                    "unknown field '{}', expected one of {}",
+                    field,
+                    QUERY_FIELDS.join(", ")
+                ),
+            }
+        }
+        QueryParserError::ExpectedInt(_) => {
+            "a numeric field needs a whole number, e.g. ordinal:12".to_owned()
+        }
+        QueryParserError::FieldNotIndexed(field) => {
+            format!("field '{}' can't be searched", field)
+        }
+        QueryParserError::AllButQueryForbidden => {
+            "a query can't only exclude terms; add a term to search for".to_owned()
+        }
+        QueryParserError::PhrasePrefixRequiresAtLeastTwoTerms { phrase, .. } => {
+            format!("the phrase prefix {:?} needs at least two words", phrase)
+        }
+        error => error.to_string(),
+    }
+}
+
+/// The error for `query` failing to parse with `source`, described as by `describe`.
+pub(crate) fn error(source: QueryParserError, query: &str) -> DictionaryError {
+    DictionaryError::QueryParse {
+        message: describe(&source, query),
+        source,
+    }
+}
+
+/// The Levenshtein distance between `a` and `b`, counting characters.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a != *b);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
@@ -0,0 +1,58 @@
+//! Transliterating Anglo-Saxon futhorc runes to the Latin letters the dictionary is written in.
+
+/// The Latin spelling of each futhorc rune, in the order of the futhorc row.
+const FUTHORC: &[(char, &str)] = &[
+    ('ᚠ', "f"),
+    ('ᚢ', "u"),
+    ('ᚦ', "þ"),
+    ('ᚩ', "o"),
+    ('ᚱ', "r"),
+    ('ᚳ', "c"),
+    ('ᚷ', "g"),
+    ('ᚹ', "w"),
+    ('ᚻ', "h"),
+    ('ᚾ', "n"),
+    ('ᛁ', "i"),
+    ('ᛄ', "j"),
+    ('ᛇ', "eo"),
+    ('ᛈ', "p"),
+    ('ᛉ', "x"),
+    ('ᛋ', "s"),
+    ('ᛏ', "t"),
+    ('ᛒ', "b"),
+    ('ᛖ', "e"),
+    ('ᛗ', "m"),
+    ('ᛚ', "l"),
+    ('ᛝ', "ng"),
+    ('ᛟ', "œ"),
+    ('ᛞ', "d"),
+    ('ᚪ', "a"),
+    ('ᚫ', "æ"),
+    ('ᚣ', "y"),
+    ('ᛠ', "ea"),
+    ('ᛡ', "io"),
+    ('ᛣ', "k"),
+    ('ᚸ', "g"),
+    ('ᛢ', "q"),
+];
+
+/// Whether `input` contains any character from the Unicode Runic block.
+pub fn is_runic(input: &str) -> bool {
+    input
+        .chars()
+        .any(|c| ('\u{16A0}'..='\u{16FF}').contains(&c))
+}
+
+/// Replace each futhorc rune in `input` with its Latin equivalent, so "ᚷᚩᛞ" becomes "god".
+/// The runic word separators ᛫, ᛬ and ᛭ become spaces; anything else is kept as is.
+pub fn transliterate_futhorc(input: &str) -> String {
+    let mut latin = String::with_capacity(input.len());
+    for c in input.chars() {
+        match FUTHORC.iter().find(|(rune, _)| *rune == c) {
+            Some((_, letters)) => latin.push_str(letters),
+            None if matches!(c, '᛫' | '᛬' | '᛭') => latin.push(' '),
+            None => latin.push(c),
+        }
+    }
+    latin
+}
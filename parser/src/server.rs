@@ -0,0 +1,71 @@
+use crate::{Dictionary, DictionaryError, Result};
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use serde::Deserialize;
+use std::sync::Arc;
+use tokio::net::{TcpListener, ToSocketAddrs};
+
+/// Serve lookups in `dict` over HTTP on `addr` until the process exits:
+///
+/// * `GET /search?q=<query>&limit=<n>` searches headwords and definitions, as
+///   `Dictionary::search` does.
+/// * `GET /define?q=<word>&limit=<n>` defines a headword, as `Dictionary::define` does.
+///
+/// Both respond with a JSON array of entries. Malformed queries are answered with
+/// `400 Bad Request` and a JSON object whose `error` describes the problem.
+pub async fn serve(dict: Dictionary, addr: impl ToSocketAddrs) -> Result<()> {
+    let listener = TcpListener::bind(addr)
+        .await
+        .map_err(|e| DictionaryError::io("Couldn't bind server address", e))?;
+    serve_on(dict, listener).await
+}
+
+pub(crate) async fn serve_on(dict: Dictionary, listener: TcpListener) -> Result<()> {
+    axum::serve(listener, router(dict))
+        .await
+        .map_err(|e| DictionaryError::io("Server failed", e))
+}
+
+fn router(dict: Dictionary) -> Router {
+    Router::new()
+        .route("/search", get(search))
+        .route("/define", get(define))
+        .with_state(Arc::new(dict))
+}
+
+#[derive(Deserialize)]
+struct Lookup {
+    q: String,
+    limit: Option<usize>,
+}
+
+async fn search(State(dict): State<Arc<Dictionary>>, Query(lookup): Query<Lookup>) -> Response {
+    respond(dict.search(&lookup.q, lookup.limit))
+}
+
+async fn define(State(dict): State<Arc<Dictionary>>, Query(lookup): Query<Lookup>) -> Response {
+    respond(dict.define(&lookup.q, lookup.limit))
+}
+
+fn respond<T: serde::Serialize>(result: Result<T>) -> Response {
+    match result {
+        Ok(body) => Json(body).into_response(),
+        Err(e) => {
+            let status = match e {
+                DictionaryError::QueryParse { .. } | DictionaryError::InvalidQuery(_) => {
+                    StatusCode::BAD_REQUEST
+                }
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            let error = match std::error::Error::source(&e) {
+                Some(source) => format!("{}: {}", e, source),
+                None => e.to_string(),
+            };
+            let body = serde_json::json!({ "error": error });
+            (status, Json(body)).into_response()
+        }
+    }
+}
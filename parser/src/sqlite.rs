@@ -0,0 +1,37 @@
+//! Writing entries to a SQLite database, for queries the tantivy syntax can't express.
+
+use crate::{Entry, Result};
+use rusqlite::{params, Connection};
+use std::path::Path;
+
+/// Create an `entries` table in the database at `path`, creating the file if needed, and
+/// insert every entry in one transaction.
+pub(crate) fn write(path: &Path, entries: impl Iterator<Item = Result<Entry>>) -> Result<()> {
+    let mut connection = Connection::open(path)?;
+    let transaction = connection.transaction()?;
+    transaction.execute_batch(
+        "CREATE TABLE entries (
+            word TEXT NOT NULL,
+            definition TEXT NOT NULL,
+            part_of_speech TEXT,
+            id TEXT
+        );
+        CREATE INDEX entries_word ON entries (word);",
+    )?;
+    {
+        let mut insert = transaction.prepare(
+            "INSERT INTO entries (word, definition, part_of_speech, id) VALUES (?1, ?2, ?3, ?4)",
+        )?;
+        for entry in entries {
+            let entry = entry?;
+            insert.execute(params![
+                entry.word,
+                entry.definition,
+                entry.part_of_speech,
+                entry.id
+            ])?;
+        }
+    }
+    transaction.commit()?;
+    Ok(())
+}
@@ -0,0 +1,72 @@
+//! Writing entries as a StarDict dictionary, for GoldenDict, KOReader and similar readers.
+
+use crate::{DictionaryError, Entry, Result};
+use log::warn;
+use std::cmp::Ordering;
+use std::path::Path;
+
+/// The file name, without extension, of each of the files written.
+pub(crate) const FILE_STEM: &str = "anglo-saxon-dictionary";
+const BOOK_NAME: &str = "Anglo-Saxon Dictionary";
+/// Headwords must fit the 256 byte buffer readers use, including the terminating NUL.
+const MAX_WORD_BYTES: usize = 255;
+
+/// Write `entries` to the `.ifo`, `.idx` and `.dict` files of a StarDict dictionary in `dir`,
+/// creating it if needed.
+pub(crate) fn write(dir: &Path, entries: impl Iterator<Item = Result<Entry>>) -> Result<()> {
+    let mut entries = entries
+        .filter(|entry| match entry {
+            Ok(entry) if entry.word.len() > MAX_WORD_BYTES || entry.word.contains('\0') => {
+                warn!("Skipping headword StarDict can't hold: {:?}", entry.word);
+                false
+            }
+            _ => true,
+        })
+        .collect::<Result<Vec<_>>>()?;
+    entries.sort_by(|a, b| compare(&a.word, &b.word));
+
+    let mut idx = Vec::new();
+    let mut dict = Vec::new();
+    for entry in &entries {
+        let definition = match &entry.part_of_speech {
+            Some(part_of_speech) => format!("({}) {}", part_of_speech, entry.definition),
+            None => entry.definition.clone(),
+        };
+        let offset = u32::try_from(dict.len()).map_err(|_| {
+            let e = std::io::Error::new(std::io::ErrorKind::InvalidData, "over 4 GiB");
+            DictionaryError::io("Too many definitions for a StarDict dictionary", e)
+        })?;
+        idx.extend_from_slice(entry.word.as_bytes());
+        idx.push(0);
+        idx.extend_from_slice(&offset.to_be_bytes());
+        idx.extend_from_slice(&(definition.len() as u32).to_be_bytes());
+        dict.extend_from_slice(definition.as_bytes());
+    }
+    let ifo = format!(
+        "StarDict's dict ifo file\nversion=2.4.2\nbookname={}\nwordcount={}\nidxfilesize={}\nsametypesequence=m\n",
+        BOOK_NAME,
+        entries.len(),
+        idx.len()
+    );
+
+    std::fs::create_dir_all(dir).map_err(|e| {
+        DictionaryError::io(format!("Couldn't create directory {}", dir.display()), e)
+    })?;
+    for (extension, contents) in [("ifo", ifo.as_bytes()), ("idx", &idx), ("dict", &dict)] {
+        let path = dir.join(format!("{}.{}", FILE_STEM, extension));
+        std::fs::write(&path, contents)
+            .map_err(|e| DictionaryError::io(format!("Couldn't write {}", path.display()), e))?;
+    }
+    Ok(())
+}
+
+/// The order StarDict readers binary search the index in: ASCII case-insensitively, then
+/// byte by byte to break ties.
+fn compare(a: &str, b: &str) -> Ordering {
+    let folded = |s: &str| {
+        s.bytes()
+            .map(|b| b.to_ascii_lowercase())
+            .collect::<Vec<_>>()
+    };
+    folded(a).cmp(&folded(b)).then_with(|| a.cmp(b))
+}
@@ -0,0 +1,103 @@
+use crate::fold;
+use tantivy::tokenizer::{
+    LowerCaser, RemoveLongFilter, SimpleTokenizer, TextAnalyzer, Token, TokenFilter, TokenStream,
+    Tokenizer,
+};
+use tantivy::Index;
+
+/// The tokenizer that stems Old English inflections.
+pub(crate) const OLD_ENGLISH: &str = "old_english";
+/// Like [`OLD_ENGLISH`], also folding þ, ð and æ as [`fold`] does.
+pub(crate) const OLD_ENGLISH_FOLDED: &str = "old_english_folded";
+
+/// Inflectional endings stripped from tokens, longest first.
+const ENDINGS: [&str; 4] = ["as", "um", "an", "e"];
+
+/// The shortest stem an ending is stripped down to, so that short words like "stan" aren't
+/// mistaken for an inflection of "st".
+const MIN_STEM_CHARS: usize = 3;
+
+/// Tokenizers aren't stored with an index, so they're registered on every index opened or
+/// created.
+pub(crate) fn register(index: &Index) {
+    for (name, fold) in [(OLD_ENGLISH, false), (OLD_ENGLISH_FOLDED, true)] {
+        let analyzer = TextAnalyzer::builder(SimpleTokenizer::default())
+            .filter(RemoveLongFilter::limit(40))
+            .filter(LowerCaser)
+            .filter(OldEnglishStemmer { fold })
+            .build();
+        index.tokenizers().register(name, analyzer);
+    }
+}
+
+/// Strip a common inflectional ending from a lowercased word.
+pub(crate) fn stem(word: &str) -> &str {
+    ENDINGS
+        .iter()
+        .filter_map(|ending| word.strip_suffix(ending))
+        .find(|stem| stem.chars().count() >= MIN_STEM_CHARS)
+        .unwrap_or(word)
+}
+
+#[derive(Clone)]
+struct OldEnglishStemmer {
+    fold: bool,
+}
+
+impl TokenFilter for OldEnglishStemmer {
+    type Tokenizer<T: Tokenizer> = OldEnglishStemmerFilter<T>;
+
+    fn transform<T: Tokenizer>(self, tokenizer: T) -> Self::Tokenizer<T> {
+        OldEnglishStemmerFilter {
+            tokenizer,
+            fold: self.fold,
+        }
+    }
+}
+
+#[derive(Clone)]
+struct OldEnglishStemmerFilter<T> {
+    tokenizer: T,
+    fold: bool,
+}
+
+impl<T: Tokenizer> Tokenizer for OldEnglishStemmerFilter<T> {
+    type TokenStream<'a> = OldEnglishStemmerStream<T::TokenStream<'a>>;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> Self::TokenStream<'a> {
+        OldEnglishStemmerStream {
+            tail: self.tokenizer.token_stream(text),
+            fold: self.fold,
+        }
+    }
+}
+
+struct OldEnglishStemmerStream<T> {
+    tail: T,
+    fold: bool,
+}
+
+impl<T: TokenStream> TokenStream for OldEnglishStemmerStream<T> {
+    fn advance(&mut self) -> bool {
+        if !self.tail.advance() {
+            return false;
+        }
+        let token = self.tail.token_mut();
+        if self.fold {
+            token.text = fold(&token.text);
+        }
+        let stemmed = stem(&token.text);
+        if stemmed.len() != token.text.len() {
+            token.text = stemmed.to_owned();
+        }
+        true
+    }
+
+    fn token(&self) -> &Token {
+        self.tail.token()
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        self.tail.token_mut()
+    }
+}
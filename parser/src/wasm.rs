@@ -0,0 +1,33 @@
+//! A `wasm-bindgen` wrapper for looking words up in the browser.
+
+use crate::{parse_str, Dictionary, Entry};
+use wasm_bindgen::prelude::*;
+
+/// A dictionary indexed in memory from HTML, exposed to JavaScript. Results are JSON arrays of
+/// entries, as the CLI's `--format json` prints them.
+#[wasm_bindgen]
+pub struct WasmDictionary(Dictionary);
+
+#[wasm_bindgen]
+impl WasmDictionary {
+    /// Parse and index the dictionary HTML, as `parse_str` does.
+    #[wasm_bindgen(constructor)]
+    pub fn new(html: &str) -> Result<WasmDictionary, JsError> {
+        Ok(WasmDictionary(parse_str(html)?))
+    }
+
+    /// Find entries whose headword or definition matches `query`, as `Dictionary::search`
+    /// does.
+    pub fn search(&self, query: &str, limit: Option<usize>) -> Result<String, JsError> {
+        to_json(&self.0.search(query, limit)?)
+    }
+
+    /// Find entries for the headword `word`, as `Dictionary::define` does.
+    pub fn define(&self, word: &str, limit: Option<usize>) -> Result<String, JsError> {
+        to_json(&self.0.define(word, limit)?)
+    }
+}
+
+fn to_json(entries: &[Entry]) -> Result<String, JsError> {
+    Ok(serde_json::to_string(entries)?)
+}